@@ -0,0 +1,8 @@
+// Run with `cargo +nightly fuzz run verify_hmac` from the crate root
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    bernie_hmac::verify_hmac_target(data);
+});
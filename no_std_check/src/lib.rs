@@ -0,0 +1,38 @@
+// A no_std consumer of the KDFs. It only compiles if hkdf and pbkdf2 build
+// without std, and the test checks that the no_std build still derives correctly
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use bernie_hmac::hkdf::{hkdf_expand, hkdf_extract};
+use bernie_hmac::KdfError;
+
+// Derives an output key with HKDF extract then expand
+pub fn derive(salt: &[u8], ikm: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, KdfError> {
+    let prk = hkdf_extract(salt, ikm);
+    hkdf_expand(&prk, info, length)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bernie_hmac::hex;
+
+    // RFC 5869 test case 1
+    #[test]
+    fn test_derive_rfc5869_case_1() {
+        let ikm = [0x0b_u8; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+
+        let okm = derive(&salt, &ikm, &info, 42).unwrap();
+        assert_eq!(
+            hex::encode(&okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+        assert!(derive(&salt, &ikm, &info, 255 * 32 + 1).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+// The hash algorithms this crate can be built with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Sha224,
+    Sha256,
+    Sha512,
+    Sha512_256,
+}
+
+impl Algorithm {
+    // The conventional name of the algorithm, e.g. for advertising capabilities
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Sha224 => "SHA-224",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha512 => "SHA-512",
+            Algorithm::Sha512_256 => "SHA-512/256",
+        }
+    }
+}
+
+/// Lists the algorithms available in this build.
+///
+/// SHA-224 and SHA-256 are always present. SHA-512 and SHA-512/256 are listed
+/// only when the `sha512` feature is enabled, as decided by `cfg!` at compile time.
+pub fn supported_algorithms() -> &'static [Algorithm] {
+    if cfg!(feature = "sha512") {
+        &[Algorithm::Sha224, Algorithm::Sha256, Algorithm::Sha512, Algorithm::Sha512_256]
+    } else {
+        &[Algorithm::Sha224, Algorithm::Sha256]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_algorithms() {
+        let algorithms = supported_algorithms();
+
+        assert!(algorithms.contains(&Algorithm::Sha256));
+        assert!(algorithms.contains(&Algorithm::Sha224));
+        assert_eq!(algorithms.contains(&Algorithm::Sha512), cfg!(feature = "sha512"));
+        assert_eq!(algorithms.contains(&Algorithm::Sha512_256), cfg!(feature = "sha512"));
+        assert_eq!(Algorithm::Sha256.name(), "SHA-256");
+        assert_eq!(Algorithm::Sha224.name(), "SHA-224");
+        assert_eq!(Algorithm::Sha512_256.name(), "SHA-512/256");
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::{Duration, Instant};
+
+use crate::utils::ct_equal;
+use crate::Hmac;
+
+/// An HMAC verifier that stops verifying after repeated failures.
+///
+/// After `threshold` consecutive failed verifications the breaker trips and
+/// `verify` returns `false` without computing anything until `cooldown` has
+/// passed. A successful verification resets the failure count. When the tag
+/// is checked, the comparison is constant time as in `verify_hmac`.
+pub struct HmacVerifierWithBackoff {
+    mac: Hmac,
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    tripped_at: Option<Instant>,
+}
+
+impl HmacVerifierWithBackoff {
+    // Panics if threshold is zero
+    pub fn new(key: &[u8], threshold: u32, cooldown: Duration) -> Self {
+        assert!(threshold > 0, "failure threshold must be non-zero");
+        HmacVerifierWithBackoff {
+            mac: Hmac::new(key),
+            threshold,
+            cooldown,
+            failures: 0,
+            tripped_at: None,
+        }
+    }
+
+    #[must_use]
+    pub fn verify(&mut self, data: &[u8], received_mac_tag: &[u8]) -> bool {
+        if let Some(tripped_at) = self.tripped_at {
+            if tripped_at.elapsed() < self.cooldown {
+                return false;
+            }
+            // The cooldown has passed, so start counting afresh
+            self.tripped_at = None;
+            self.failures = 0;
+        }
+
+        let mut mac = self.mac.clone();
+        mac.update(data);
+        let computed: [u8; 32] = mac.finish();
+
+        if ct_equal(&computed, received_mac_tag) {
+            self.failures = 0;
+            return true;
+        }
+        self.failures += 1;
+        if self.failures >= self.threshold {
+            self.tripped_at = Some(Instant::now());
+        }
+        false
+    }
+
+    // Whether verify is currently refusing to check tags
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some_and(|tripped_at| tripped_at.elapsed() < self.cooldown)
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac;
+
+    #[test]
+    fn test_repeated_failures_trip_breaker() {
+        let key = b"backoff key";
+        let tag = hmac(b"message", key);
+        let mut verifier = HmacVerifierWithBackoff::new(key, 3, Duration::from_secs(3600));
+
+        for _ in 0..3 {
+            assert!(!verifier.verify(b"message", b"forged tag"));
+        }
+        assert!(verifier.is_tripped());
+
+        // Even the correct tag is refused while tripped
+        assert!(!verifier.verify(b"message", &tag));
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let key = b"backoff key";
+        let tag = hmac(b"message", key);
+        let mut verifier = HmacVerifierWithBackoff::new(key, 3, Duration::from_secs(3600));
+
+        assert!(!verifier.verify(b"message", b"forged tag"));
+        assert!(!verifier.verify(b"message", b"forged tag"));
+        assert!(verifier.verify(b"message", &tag));
+        assert_eq!(verifier.consecutive_failures(), 0);
+
+        assert!(!verifier.verify(b"message", b"forged tag"));
+        assert!(!verifier.is_tripped());
+    }
+
+    #[test]
+    fn test_breaker_resets_after_cooldown() {
+        let key = b"backoff key";
+        let tag = hmac(b"message", key);
+        let mut verifier = HmacVerifierWithBackoff::new(key, 1, Duration::ZERO);
+
+        assert!(!verifier.verify(b"message", b"forged tag"));
+        assert!(!verifier.is_tripped());
+        assert!(verifier.verify(b"message", &tag));
+    }
+}
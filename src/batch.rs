@@ -0,0 +1,346 @@
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+use crate::error::Error;
+use crate::utils::{padded_len, padding, parse, parse_block, sigma_256_1, sigma_256_0, Sigma_256_0, Sigma_256_1, ch, maj};
+use crate::constants::{INITIAL_HASH, PRIME_CUBES, BLOCKSIZE};
+use crate::{compress, state_to_bytes, Sha256};
+
+// Number of lanes the interleaved kernel processes together
+const LANES: usize = 4;
+
+/// Hashes several equal-length messages together, one block index at a time.
+///
+/// Every input must have the same length, so the padding is computed once and
+/// shared. The outer loop walks the block index and the inner loop walks the
+/// lanes, which is the layout a SIMD or GPU kernel would use. Each result
+/// equals `hash()` of the corresponding input.
+pub fn hash_batch_same_length(inputs: &[&[u8]]) -> Result<Vec<[u8; 32]>, Error> {
+    let Some(first) = inputs.first() else {
+        return Ok(Vec::new());
+    };
+    let len = first.len();
+    if let Some(other) = inputs.iter().find(|input| input.len() != len) {
+        return Err(Error::LengthMismatch { expected: len, got: other.len() });
+    }
+
+    // The padding only depends on the length, so it is shared by every lane
+    let padding = padding(len as u64)?;
+
+    // Parse each lane into its blocks
+    let lanes: Vec<Vec<[u32; 16]>> = inputs
+        .iter()
+        .map(|input| {
+            let mut padded = Vec::with_capacity(len + padding.len());
+            padded.extend_from_slice(input);
+            padded.extend_from_slice(&padding);
+            parse(&padded).expect("padding always fills whole blocks")
+        })
+        .collect();
+
+    // Advance every lane through block i before moving to block i + 1
+    let num_blocks = (len + padding.len()) / 64;
+    let mut states = vec![INITIAL_HASH; lanes.len()];
+    for i in 0..num_blocks {
+        for (state, blocks) in states.iter_mut().zip(lanes.iter()) {
+            compress(state, &blocks[i]);
+        }
+    }
+
+    Ok(states.iter().map(state_to_bytes).collect())
+}
+
+// Hashes each input into the matching slot of out. Slots past inputs.len() are left as they are
+pub fn hash_all_into(inputs: &[&[u8]], out: &mut [[u8; 32]]) -> Result<(), Error> {
+    if out.len() < inputs.len() {
+        return Err(Error::BufferTooSmall { needed: inputs.len(), got: out.len() });
+    }
+
+    for (slot, input) in out.iter_mut().zip(inputs.iter()) {
+        let mut hasher = Sha256::new();
+        hasher.update(input);
+        *slot = hasher.finish();
+    }
+
+    Ok(())
+}
+
+/// Returns the digest of every chunk, hashing each distinct chunk only once.
+///
+/// SHA-256 is sequential, so deduplication cannot speed up one digest over the
+/// whole stream. It does help when a caller wants per-chunk digests, as backup
+/// tools do: repeated chunks are looked up by content instead of rehashed.
+pub fn chunk_digests_dedup(chunks: &[&[u8]]) -> Vec<[u8; 32]> {
+    dedup_digests(chunks).0
+}
+
+// Does the work of chunk_digests_dedup, also counting the compressions performed
+fn dedup_digests(chunks: &[&[u8]]) -> (Vec<[u8; 32]>, usize) {
+    let mut seen: BTreeMap<&[u8], [u8; 32]> = BTreeMap::new();
+    let mut compressions = 0;
+
+    let digests = chunks
+        .iter()
+        .map(|chunk| {
+            *seen.entry(chunk).or_insert_with(|| {
+                compressions += padded_len(chunk.len()) / BLOCKSIZE;
+                let mut hasher = Sha256::new();
+                hasher.update(chunk);
+                hasher.finish()
+            })
+        })
+        .collect();
+
+    (digests, compressions)
+}
+
+// Runs the compression function on L independent states at once. Every step is
+// written with the lane index innermost, so each operation is applied across
+// all lanes before moving on, which is the shape SIMD registers want.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn compress_lanes<const L: usize>(states: &mut [[u32; 8]; L], blocks: [&[u32; 16]; L]) {
+    // Build the message schedules side by side
+    let mut w = [[0_u32; L]; 64];
+    for t in 0..16 {
+        for l in 0..L {
+            w[t][l] = blocks[l][t];
+        }
+    }
+    for t in 16..64 {
+        for l in 0..L {
+            w[t][l] = sigma_256_1(w[t - 2][l])
+                        .wrapping_add(w[t - 7][l])
+                        .wrapping_add(sigma_256_0(w[t - 15][l]))
+                        .wrapping_add(w[t - 16][l]);
+        }
+    }
+
+    // Transpose the states into one array of lanes per working variable
+    let mut vars = [[0_u32; L]; 8];
+    for (l, state) in states.iter().enumerate() {
+        for (i, word) in state.iter().enumerate() {
+            vars[i][l] = *word;
+        }
+    }
+
+    for t in 0..64 {
+        let [a, b, c, d, e, f, g, h] = vars;
+        let mut next = [[0_u32; L]; 8];
+        for l in 0..L {
+            let t1 = h[l].wrapping_add(Sigma_256_1(e[l]))
+                        .wrapping_add(ch(e[l], f[l], g[l]))
+                        .wrapping_add(PRIME_CUBES[t])
+                        .wrapping_add(w[t][l]);
+            let t2 = Sigma_256_0(a[l]).wrapping_add(maj(a[l], b[l], c[l]));
+
+            next[0][l] = t1.wrapping_add(t2);
+            next[1][l] = a[l];
+            next[2][l] = b[l];
+            next[3][l] = c[l];
+            next[4][l] = d[l].wrapping_add(t1);
+            next[5][l] = e[l];
+            next[6][l] = f[l];
+            next[7][l] = g[l];
+        }
+        vars = next;
+    }
+
+    // Fold the working variables back into each state
+    for (l, state) in states.iter_mut().enumerate() {
+        for (i, word) in state.iter_mut().enumerate() {
+            *word = word.wrapping_add(vars[i][l]);
+        }
+    }
+}
+
+/// A set of independent incremental hashers advanced in lockstep.
+///
+/// `step_all` feeds one input to every lane. Whenever four lanes each have a
+/// whole block ready, those blocks are compressed together by an interleaved
+/// kernel (the multi-buffer technique); anything left over takes the scalar
+/// path. Each lane's digest is exactly that of a standalone `Sha256`.
+pub struct MultiSha256 {
+    lanes: Vec<Sha256>,
+}
+
+impl MultiSha256 {
+    pub fn new(lanes: usize) -> Self {
+        MultiSha256 { lanes: vec![Sha256::new(); lanes] }
+    }
+
+    pub fn lanes(&self) -> usize {
+        self.lanes.len()
+    }
+
+    // Feeds inputs[i] into lane i. The number of inputs must match the number of lanes
+    pub fn step_all(&mut self, inputs: &[&[u8]]) -> Result<(), Error> {
+        if inputs.len() != self.lanes.len() {
+            return Err(Error::LengthMismatch { expected: self.lanes.len(), got: inputs.len() });
+        }
+
+        // Check every lane's new length before any lane is touched
+        for (lane, input) in self.lanes.iter().zip(inputs.iter()) {
+            lane.checked_total_len(input.len())?;
+        }
+
+        // Complete any partially buffered block so the rest of each input is block-aligned
+        let mut remaining: Vec<&[u8]> = Vec::with_capacity(inputs.len());
+        for (lane, input) in self.lanes.iter_mut().zip(inputs.iter()) {
+            let head = if lane.buffer_len > 0 { (BLOCKSIZE - lane.buffer_len).min(input.len()) } else { 0 };
+            lane.update(&input[..head]);
+            remaining.push(&input[head..]);
+        }
+
+        for (group, inputs) in self.lanes.chunks_mut(LANES).zip(remaining.chunks_mut(LANES)) {
+            let mut done = 0;
+
+            // Compress blocks four lanes at a time while every lane in the group has one
+            if group.len() == LANES {
+                let common = inputs.iter().map(|input| input.len() / BLOCKSIZE).min().unwrap_or(0);
+                let mut states = [[0_u32; 8]; LANES];
+                for (state, lane) in states.iter_mut().zip(group.iter()) {
+                    *state = lane.state;
+                }
+
+                for i in 0..common {
+                    let range = i * BLOCKSIZE..(i + 1) * BLOCKSIZE;
+                    let blocks: [[u32; 16]; LANES] = core::array::from_fn(|l| parse_block(&inputs[l][range.clone()]));
+                    compress_lanes(&mut states, core::array::from_fn(|l| &blocks[l]));
+                }
+
+                for (state, lane) in states.iter().zip(group.iter_mut()) {
+                    lane.state = *state;
+                    // Within the limit, as every lane's full input was checked above
+                    lane.total_len = lane.checked_total_len(common * BLOCKSIZE)?;
+                }
+                done = common * BLOCKSIZE;
+            }
+
+            // Whatever could not be interleaved goes through the ordinary update
+            for (lane, input) in group.iter_mut().zip(inputs.iter()) {
+                lane.update(&input[done..]);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Finalizes every lane independently, returning the digests in lane order
+    #[must_use]
+    pub fn finalize(self) -> Vec<Vec<u8>> {
+        self.lanes.into_iter().map(Sha256::finalize).collect()
+    }
+
+    // Splits the set back into its individual hashers
+    pub fn into_hashers(self) -> Vec<Sha256> {
+        self.lanes
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_hash_batch_matches_hash() {
+        for len in [0, 3, 55, 56, 64, 130] {
+            let messages: Vec<Vec<u8>> = (0..5_u8).map(|lane| vec![lane.wrapping_mul(37); len]).collect();
+            let inputs: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+            let digests = hash_batch_same_length(&inputs).unwrap();
+
+            assert_eq!(digests.len(), inputs.len());
+            for (digest, input) in digests.iter().zip(inputs.iter()) {
+                assert_eq!(digest.to_vec(), hash(input));
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_batch_rejects_mismatched_lengths() {
+        let inputs: [&[u8]; 3] = [b"abc", b"def", b"ghij"];
+
+        assert_eq!(hash_batch_same_length(&inputs), Err(Error::LengthMismatch { expected: 3, got: 4 }));
+        assert_eq!(hash_batch_same_length(&[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_hash_all_into() {
+        let inputs: [&[u8]; 3] = [b"", b"abc", &[0x11; 100]];
+        let mut out = [[0xff_u8; 32]; 4];
+        hash_all_into(&inputs, &mut out).unwrap();
+
+        for (slot, input) in out.iter().zip(inputs.iter()) {
+            assert_eq!(slot.to_vec(), hash(input));
+        }
+        assert_eq!(out[3], [0xff_u8; 32]);
+
+        let mut short = [[0_u8; 32]; 2];
+        assert_eq!(hash_all_into(&inputs, &mut short), Err(Error::BufferTooSmall { needed: 3, got: 2 }));
+    }
+
+    #[test]
+    fn test_chunk_digests_dedup() {
+        let block_a = vec![0xaa_u8; 4096];
+        let block_b = vec![0xbb_u8; 4096];
+        let chunks: [&[u8]; 6] = [&block_a, &block_b, &block_a, &block_a, b"tail", &block_b];
+
+        let (digests, compressions) = dedup_digests(&chunks);
+        assert_eq!(digests, chunk_digests_dedup(&chunks));
+        for (digest, chunk) in digests.iter().zip(chunks.iter()) {
+            assert_eq!(digest.to_vec(), hash(chunk));
+        }
+
+        // Only block_a, block_b and the tail were actually compressed
+        let naive: usize = chunks.iter().map(|chunk| padded_len(chunk.len()) / BLOCKSIZE).sum();
+        assert_eq!(compressions, 2 * padded_len(4096) / BLOCKSIZE + 1);
+        assert!(compressions < naive);
+    }
+
+    #[test]
+    fn test_multi_sha256_four_lanes() {
+        let messages: [Vec<u8>; 4] = [
+            b"first lane".repeat(30),
+            b"second".repeat(51),
+            Vec::new(),
+            (0..1000_u32).map(|i| i as u8).collect(),
+        ];
+
+        let mut multi = MultiSha256::new(4);
+        let mut singles = vec![Sha256::new(); 4];
+        let mut offset = 0;
+        for step in [0, 5, 64, 100, 128, 1, 1000] {
+            let inputs: Vec<&[u8]> = messages
+                .iter()
+                .map(|m| &m[offset.min(m.len())..(offset + step).min(m.len())])
+                .collect();
+            multi.step_all(&inputs).unwrap();
+            for (single, input) in singles.iter_mut().zip(inputs.iter()) {
+                single.update(input);
+            }
+            offset += step;
+        }
+
+        let expected: Vec<Vec<u8>> = singles.into_iter().map(Sha256::finalize).collect();
+        assert_eq!(multi.finalize(), expected);
+        for (digest, message) in expected.iter().zip(messages.iter()) {
+            assert_eq!(digest, &hash(message));
+        }
+    }
+
+    #[test]
+    fn test_multi_sha256_uneven_lane_count() {
+        let messages: Vec<Vec<u8>> = (0..6_u8).map(|i| vec![i; 200 + i as usize]).collect();
+        let inputs: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+        let mut multi = MultiSha256::new(6);
+        multi.step_all(&inputs).unwrap();
+        for (digest, message) in multi.finalize().iter().zip(messages.iter()) {
+            assert_eq!(digest, &hash(message));
+        }
+
+        let mut multi = MultiSha256::new(2);
+        assert_eq!(multi.step_all(&inputs), Err(Error::LengthMismatch { expected: 2, got: 6 }));
+    }
+}
@@ -0,0 +1,76 @@
+use core::hash::{BuildHasher, Hasher};
+
+use crate::Sha256;
+
+/// A keyed `BuildHasher` for maps that hold adversarial keys.
+///
+/// Every hasher it builds starts from SHA-256 state that has already absorbed
+/// the 32-byte key, so collisions can't be precomputed without knowing it.
+/// Much slower than the default SipHash, in exchange for a cryptographic hash.
+#[derive(Clone)]
+pub struct Sha256BuildHasher {
+    seeded: Sha256,
+}
+
+impl Sha256BuildHasher {
+    pub fn new(key: [u8; 32]) -> Self {
+        let mut seeded = Sha256::new();
+        seeded.update(&key);
+        Sha256BuildHasher { seeded }
+    }
+}
+
+impl BuildHasher for Sha256BuildHasher {
+    type Hasher = Sha256Hasher;
+
+    fn build_hasher(&self) -> Sha256Hasher {
+        Sha256Hasher { inner: self.seeded.clone() }
+    }
+}
+
+/// The `Hasher` produced by `Sha256BuildHasher`.
+#[derive(Clone)]
+pub struct Sha256Hasher {
+    inner: Sha256,
+}
+
+impl Hasher for Sha256Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes);
+    }
+
+    // Folds the digest to 64 bits by XORing its four 8-byte words
+    fn finish(&self) -> u64 {
+        let digest = self.inner.snapshot_digest();
+        digest
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .fold(0, |acc, word| acc ^ word)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_different_keys_hash_differently() {
+        let a = Sha256BuildHasher::new([1_u8; 32]);
+        let b = Sha256BuildHasher::new([2_u8; 32]);
+
+        assert_ne!(a.hash_one("same input"), b.hash_one("same input"));
+        assert_eq!(a.hash_one("same input"), a.hash_one("same input"));
+    }
+
+    #[test]
+    fn test_usable_as_map_hasher() {
+        let mut map = HashMap::with_hasher(Sha256BuildHasher::new([7_u8; 32]));
+        map.insert("alice", 1);
+        map.insert("bob", 2);
+
+        assert_eq!(map.get("alice"), Some(&1));
+        assert_eq!(map.get("bob"), Some(&2));
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::hmac;
+
+/// Computes the HMAC of `value` over its canonical JSON encoding.
+///
+/// The encoding is compact JSON (no insignificant whitespace) with the keys of
+/// every object sorted by their UTF-8 bytes, so serializing the same logical
+/// value twice always yields the same bytes and therefore the same tag. Strings
+/// and numbers are written exactly as `serde_json` writes them.
+pub fn hmac_value<T: Serialize>(value: &T, key: &[u8]) -> Result<Vec<u8>, Error> {
+    let bytes = to_canonical_json(value)?;
+    Ok(hmac(&bytes, key))
+}
+
+// Serializes a value to canonical JSON bytes
+pub(crate) fn to_canonical_json<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let value = serde_json::to_value(value).map_err(|e| Error::Serialization(e.to_string()))?;
+    let mut out = Vec::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+// Writes the value recursively, sorting object keys instead of relying on
+// the map ordering serde_json happens to be built with
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> Result<(), Error> {
+    match value {
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+            out.push(b'{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, k).map_err(|e| Error::Serialization(e.to_string()))?;
+                out.push(b':');
+                write_canonical(v, out)?;
+            }
+            out.push(b'}');
+        }
+        scalar => {
+            serde_json::to_writer(&mut *out, scalar).map_err(|e| Error::Serialization(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize)]
+    struct Order {
+        id: u32,
+        customer: String,
+        items: Vec<String>,
+        metadata: HashMap<String, String>,
+    }
+
+    fn sample_order() -> Order {
+        let mut metadata = HashMap::new();
+        metadata.insert("zone".to_string(), "eu-west".to_string());
+        metadata.insert("channel".to_string(), "web".to_string());
+        metadata.insert("priority".to_string(), "high".to_string());
+
+        Order {
+            id: 42,
+            customer: "bernie".to_string(),
+            items: vec!["apple".to_string(), "pear".to_string()],
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let bytes = to_canonical_json(&sample_order()).unwrap();
+        let expected = r#"{"customer":"bernie","id":42,"items":["apple","pear"],"metadata":{"channel":"web","priority":"high","zone":"eu-west"}}"#;
+
+        assert_eq!(bytes, expected.as_bytes());
+    }
+
+    #[test]
+    fn test_hmac_value_json_round_trip() {
+        let key = b"structured signing key";
+        let order = sample_order();
+        let tag = hmac_value(&order, key).unwrap();
+
+        // Pretty-print to reorder whitespace and let the HashMap re-insert its keys
+        let json = serde_json::to_string_pretty(&order).unwrap();
+        let decoded: Order = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(hmac_value(&decoded, key).unwrap(), tag);
+        assert_eq!(tag, hmac(to_canonical_json(&order).unwrap(), key));
+    }
+}
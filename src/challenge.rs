@@ -0,0 +1,46 @@
+use rand::RngCore;
+
+use crate::{hmac, verify_hmac};
+
+// Length in bytes of a generated challenge
+const CHALLENGE_LEN: usize = 32;
+
+/// Generates a random 32-byte challenge and the response a key holder must return.
+///
+/// Returns `(challenge, expected_response)` where the response is
+/// `hmac(challenge, key)`. The challenge comes from the thread-local CSPRNG.
+/// Check what the client sends back with `verify_response`.
+#[must_use]
+pub fn make_challenge(key: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut challenge = vec![0_u8; CHALLENGE_LEN];
+    rand::thread_rng().fill_bytes(&mut challenge);
+    let response = hmac(&challenge, key);
+    (challenge, response)
+}
+
+// Checks a client's response to a challenge in constant time
+#[must_use]
+pub fn verify_response(challenge: &[u8], response: &[u8], key: &[u8]) -> bool {
+    verify_hmac(challenge, response, key)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_response() {
+        let key = b"shared secret";
+        let (challenge, expected) = make_challenge(key);
+
+        assert_eq!(challenge.len(), CHALLENGE_LEN);
+        assert!(verify_response(&challenge, &hmac(&challenge, key), key));
+        assert!(verify_response(&challenge, &expected, key));
+
+        let mut tampered = expected.clone();
+        tampered[0] ^= 1;
+        assert!(!verify_response(&challenge, &tampered, key));
+        assert_ne!(make_challenge(key).0, challenge);
+    }
+}
@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+
+use crate::Sha256;
+
+/// A hasher that takes a snapshot digest every `interval` bytes.
+///
+/// `update` returns the checkpoints crossed while absorbing that chunk as
+/// `(bytes_processed, digest)` pairs, where each digest equals `hash()` of the
+/// first `bytes_processed` bytes. The full digest still comes from `finalize`.
+#[derive(Clone)]
+pub struct CheckpointingHasher {
+    hasher: Sha256,
+    interval: u64,
+    processed: u64,
+}
+
+impl CheckpointingHasher {
+    // Panics if interval is zero
+    pub fn new(interval: u64) -> Self {
+        assert!(interval > 0, "checkpoint interval must be non-zero");
+        CheckpointingHasher {
+            hasher: Sha256::new(),
+            interval,
+            processed: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) -> Vec<(u64, [u8; 32])> {
+        let mut checkpoints = Vec::new();
+        while !data.is_empty() {
+            // Feed up to the next checkpoint boundary
+            let until_next = self.interval - self.processed % self.interval;
+            let take = until_next.min(data.len() as u64) as usize;
+            self.hasher.update(&data[..take]);
+            self.processed += take as u64;
+            data = &data[take..];
+
+            if self.processed.is_multiple_of(self.interval) {
+                checkpoints.push((self.processed, self.hasher.snapshot_digest()));
+            }
+        }
+        checkpoints
+    }
+
+    pub fn bytes_processed(&self) -> u64 {
+        self.processed
+    }
+
+    #[must_use]
+    pub fn finalize(self) -> Vec<u8> {
+        self.hasher.finalize()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_checkpoints_match_prefix_hashes() {
+        let data: Vec<u8> = (0..95_u8).collect();
+        let mut hasher = CheckpointingHasher::new(10);
+
+        let mut checkpoints = Vec::new();
+        for chunk in data.chunks(7) {
+            checkpoints.extend(hasher.update(chunk));
+        }
+
+        assert_eq!(checkpoints.len(), 9);
+        for (i, (processed, digest)) in checkpoints.iter().enumerate() {
+            assert_eq!(*processed, 10 * (i as u64 + 1));
+            assert_eq!(digest.to_vec(), hash(&data[..*processed as usize]));
+        }
+        assert_eq!(hasher.bytes_processed(), 95);
+        assert_eq!(hasher.finalize(), hash(&data));
+    }
+
+    #[test]
+    fn test_checkpoints_within_one_update() {
+        let data = [0x42_u8; 64];
+        let mut hasher = CheckpointingHasher::new(16);
+        let checkpoints = hasher.update(&data);
+
+        let offsets: Vec<u64> = checkpoints.iter().map(|(processed, _)| *processed).collect();
+        assert_eq!(offsets, vec![16, 32, 48, 64]);
+        assert!(hasher.update(&[]).is_empty());
+    }
+}
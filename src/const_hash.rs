@@ -0,0 +1,86 @@
+use crate::compress;
+use crate::constants::{BLOCKSIZE, INITIAL_HASH};
+use crate::utils::padded_len;
+
+/// Computes SHA-256 of `data` in a `const` context, such as a `const` item.
+///
+/// The padding is generated byte by byte as blocks are assembled, so nothing is
+/// allocated and the result equals `hash(data)`. Every round runs in the const
+/// evaluator, so keep inputs to a few kilobytes to stay under its step limit.
+#[must_use]
+pub const fn hash_const<const N: usize>(data: &[u8; N]) -> [u8; 32] {
+    let padded_len = padded_len(N);
+    let bit_len = (N as u64).wrapping_mul(8).to_be_bytes();
+
+    let mut state = INITIAL_HASH;
+    let mut offset = 0;
+    while offset < padded_len {
+        let mut block = [0_u32; 16];
+        let mut i = 0;
+        while i < BLOCKSIZE {
+            let pos = offset + i;
+            let byte = if pos < N {
+                data[pos]
+            } else if pos == N {
+                0x80
+            } else if pos >= padded_len - 8 {
+                bit_len[pos - (padded_len - 8)]
+            } else {
+                0
+            };
+            block[i / 4] |= (byte as u32) << (24 - 8 * (i % 4));
+            i += 1;
+        }
+        compress(&mut state, &block);
+        offset += BLOCKSIZE;
+    }
+
+    let mut digest = [0_u8; 32];
+    let mut i = 0;
+    while i < 8 {
+        let bytes = state[i].to_be_bytes();
+        digest[4 * i] = bytes[0];
+        digest[4 * i + 1] = bytes[1];
+        digest[4 * i + 2] = bytes[2];
+        digest[4 * i + 3] = bytes[3];
+        i += 1;
+    }
+    digest
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    const EXPECTED: [u8; 32] = hash_const(b"abc");
+
+    // Arrays can't be compared with == in a const context
+    const fn bytes_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+        let mut i = 0;
+        while i < 32 {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    // Checked by the compiler: the build fails if the const digest is wrong
+    const _: () = assert!(bytes_eq(&EXPECTED, &[
+        0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+        0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+    ]));
+
+    #[test]
+    fn test_hash_const_matches_hash() {
+        assert_eq!(EXPECTED.to_vec(), hash(b"abc"));
+        assert_eq!(hash_const(b"").to_vec(), hash(b""));
+        assert_eq!(hash_const(&[0x61; 55]).to_vec(), hash([0x61; 55]));
+        assert_eq!(hash_const(&[0x61; 56]).to_vec(), hash([0x61; 56]));
+        assert_eq!(hash_const(&[0x61; 64]).to_vec(), hash([0x61; 64]));
+        assert_eq!(hash_const(&[0x61; 200]).to_vec(), hash([0x61; 200]));
+    }
+}
@@ -0,0 +1,32 @@
+use alloc::string::String;
+
+use crate::hex::encode;
+use crate::hmac;
+
+/// Computes the HMAC of `data` and returns it alongside the exact signing input.
+///
+/// Returns `(tag_hex, data_hex)`: the lowercase hex tag, and a lowercase hex dump
+/// of the bytes that were MACed. When a tag disagrees with another system's, the
+/// cause is usually a different signing string, so comparing `data_hex` against
+/// the other side's input pinpoints the discrepancy.
+#[must_use]
+pub fn hmac_debug(data: &[u8], key: &[u8]) -> (String, String) {
+    let tag = hmac(data, key);
+    (encode(&tag), encode(data))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_debug_matches_hmac() {
+        let data = b"GET\n/v1/orders\ndate=2024-01-01";
+        let key = b"debug key";
+        let (tag_hex, data_hex) = hmac_debug(data, key);
+
+        assert_eq!(tag_hex, encode(&hmac(data, key)));
+        assert_eq!(data_hex, "4745540a2f76312f6f72646572730a646174653d323032342d30312d3031");
+    }
+}
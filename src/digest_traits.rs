@@ -0,0 +1,67 @@
+// Implements the RustCrypto digest traits for Sha256, so it can be used by crates
+// that are generic over digest::Digest, such as hmac and hkdf
+use digest::consts::U32;
+use digest::{FixedOutput, HashMarker, Output, OutputSizeUser, Reset, Update};
+
+use crate::Sha256;
+
+impl OutputSizeUser for Sha256 {
+    type OutputSize = U32;
+}
+
+impl Update for Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        Sha256::update(self, data);
+    }
+}
+
+impl FixedOutput for Sha256 {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finish());
+    }
+}
+
+impl Reset for Sha256 {
+    fn reset(&mut self) {
+        Sha256::reset(self);
+    }
+}
+
+// Together with Default, this makes Sha256 a digest::Digest
+impl HashMarker for Sha256 {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    // Drives the hasher only through the trait methods
+    fn trait_hash<D: Update + FixedOutput + Default>(data: &[u8]) -> Vec<u8> {
+        let mut hasher = D::default();
+        Update::update(&mut hasher, data);
+        hasher.finalize_fixed().to_vec()
+    }
+
+    #[test]
+    fn test_digest_traits_match_hash() {
+        let long = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        for data in [&b""[..], b"abc", &long[..]] {
+            assert_eq!(trait_hash::<Sha256>(data), hash(data));
+        }
+        assert_eq!(Sha256::output_size(), 32);
+    }
+
+    #[test]
+    fn test_digest_trait_and_reset() {
+        use digest::Digest;
+
+        assert_eq!(<Sha256 as Digest>::digest(b"abc").to_vec(), hash(b"abc"));
+
+        let mut hasher = <Sha256 as Digest>::new();
+        Update::update(&mut hasher, b"discarded");
+        Reset::reset(&mut hasher);
+        Update::update(&mut hasher, b"abc");
+        assert_eq!(hasher.finalize_fixed().to_vec(), hash(b"abc"));
+    }
+}
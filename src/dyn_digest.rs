@@ -0,0 +1,71 @@
+// Lets Sha256 be used as a Box<dyn digest::DynDigest>, so the algorithm can be
+// picked at runtime alongside hashers from other crates
+use alloc::boxed::Box;
+use digest::{DynDigest, InvalidBufferSize};
+
+use crate::{Sha256, OUTPUT_SIZE};
+
+impl DynDigest for Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        Sha256::update(self, data);
+    }
+
+    fn finalize_reset(&mut self) -> Box<[u8]> {
+        core::mem::take(self).finish().into()
+    }
+
+    fn finalize_into(self, buf: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if buf.len() != OUTPUT_SIZE {
+            return Err(InvalidBufferSize);
+        }
+        buf.copy_from_slice(&self.finish());
+        Ok(())
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut [u8]) -> Result<(), InvalidBufferSize> {
+        if out.len() != OUTPUT_SIZE {
+            return Err(InvalidBufferSize);
+        }
+        out.copy_from_slice(&core::mem::take(self).finish());
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        Sha256::reset(self);
+    }
+
+    fn output_size(&self) -> usize {
+        OUTPUT_SIZE
+    }
+
+    fn box_clone(&self) -> Box<dyn DynDigest> {
+        Box::new(self.clone())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_boxed_dyn_digest() {
+        let mut hasher: Box<dyn DynDigest> = Box::new(Sha256::new());
+        assert_eq!(hasher.output_size(), 32);
+
+        hasher.update(b"boxed ");
+        let fork = hasher.box_clone();
+        hasher.update(b"trait object");
+
+        assert_eq!(hasher.finalize_reset().to_vec(), hash(b"boxed trait object"));
+        assert_eq!(fork.finalize().to_vec(), hash(b"boxed "));
+
+        // After finalize_reset the hasher starts over
+        hasher.update(b"abc");
+        let mut out = [0_u8; 32];
+        hasher.finalize_into_reset(&mut out).unwrap();
+        assert_eq!(out.to_vec(), hash(b"abc"));
+        assert!(hasher.finalize_into_reset(&mut [0_u8; 31]).is_err());
+    }
+}
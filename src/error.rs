@@ -0,0 +1,167 @@
+use alloc::string::String;
+use core::fmt;
+
+// Errors returned by the fallible parts of the public API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // The value could not be serialized into its canonical byte encoding
+    Serialization(String),
+    // A batch operation required inputs of equal length but got a different one
+    LengthMismatch { expected: usize, got: usize },
+    // A caller-provided output buffer cannot hold the result
+    BufferTooSmall { needed: usize, got: usize },
+    // Message preprocessing failed
+    Hash(HashError),
+    // A truncated tag length outside 1..=32 bytes
+    TagLength { len: usize },
+    // The hasher holds bytes of an unfinished block, so its state can't be exported
+    PartialBlock { buffered: usize },
+    // One-time passwords must be 6 to 8 digits long
+    OtpDigits { digits: u32 },
+    // A TOTP time step of zero seconds
+    ZeroTimeStep,
+    // A tagged MAC version byte that names no known algorithm
+    UnknownVersion { version: u8 },
+}
+
+// Errors from preprocessing a message before compression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashError {
+    // Padding produced a bit count that is not a whole number of 512-bit blocks
+    UnalignedPadding { bits: usize },
+    // The message is too long for its bit length to fit the 64-bit length field
+    InputTooLong { len: u64 },
+    // The input to block parsing was not a whole number of 64-byte blocks
+    UnalignedInput { len: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Serialization(msg) => write!(f, "serialization failed: {}", msg),
+            Error::LengthMismatch { expected, got } => {
+                write!(f, "input length mismatch: expected {} bytes, got {}", expected, got)
+            }
+            Error::BufferTooSmall { needed, got } => {
+                write!(f, "output buffer too small: need {} bytes, got {}", needed, got)
+            }
+            Error::Hash(err) => write!(f, "{}", err),
+            Error::TagLength { len } => {
+                write!(f, "truncated tag length must be between 1 and 32 bytes, got {}", len)
+            }
+            Error::PartialBlock { buffered } => {
+                write!(f, "{} bytes of a partial block are still buffered", buffered)
+            }
+            Error::OtpDigits { digits } => {
+                write!(f, "one-time passwords must have 6 to 8 digits, got {}", digits)
+            }
+            Error::ZeroTimeStep => write!(f, "TOTP time step must be at least one second"),
+            Error::UnknownVersion { version } => write!(f, "unknown MAC version {}", version),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<HashError> for Error {
+    fn from(err: HashError) -> Self {
+        Error::Hash(err)
+    }
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::UnalignedPadding { bits } => {
+                write!(f, "padded message of {} bits is not a multiple of 512", bits)
+            }
+            HashError::InputTooLong { len } => {
+                write!(f, "input of {} bytes exceeds the SHA-256 limit of 2^64 - 1 bits", len)
+            }
+            HashError::UnalignedInput { len } => {
+                write!(f, "input of {} bytes is not a multiple of the 64-byte block size", len)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HashError {}
+
+// Errors from the key derivation functions. Only core::fmt is used, so the type is
+// available wherever the KDFs themselves can be built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfError {
+    // More output was requested than the construction can produce
+    OutputTooLong { requested: usize, max: usize },
+    // An iteration count of zero would skip the PRF entirely
+    ZeroIterations,
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdfError::OutputTooLong { requested, max } => {
+                write!(f, "requested {} bytes of output, at most {} are allowed", requested, max)
+            }
+            KdfError::ZeroIterations => write!(f, "iteration count must be at least 1"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KdfError {}
+
+// Errors from decoding a hex string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    // Hex needs two characters per byte, so the length must be even
+    OddLength { len: usize },
+    // The character at this byte index is not a hex digit
+    InvalidChar { index: usize, ch: char },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength { len } => write!(f, "hex string has odd length {}", len),
+            HexError::InvalidChar { index, ch } => {
+                write!(f, "invalid hex character {:?} at index {}", ch, index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+// Errors from verification calls that were given malformed input, as opposed to
+// a well-formed tag that simply doesn't match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    // The received tag has the wrong length to be a tag at all
+    TagLength { expected: usize, got: usize },
+    // The tag was not valid hex
+    Encoding(HexError),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::TagLength { expected, got } => {
+                write!(f, "tag must be {} bytes, got {}", expected, got)
+            }
+            VerifyError::Encoding(err) => write!(f, "malformed tag: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
+
+impl From<HexError> for VerifyError {
+    fn from(err: HexError) -> Self {
+        VerifyError::Encoding(err)
+    }
+}
@@ -0,0 +1,76 @@
+// Fuzz harness for verify_hmac, shared by the cargo-fuzz target in fuzz/ and the
+// deterministic corpus test below
+use crate::{hmac_fixed, verify_hmac, verify_hmac_checked, VerifyError};
+
+/// Runs `verify_hmac` on a `(data, tag, key)` triple carved out of arbitrary bytes.
+///
+/// The input is a length byte and that many key bytes, then a length byte and
+/// that many tag bytes, and the rest is the data; lengths past the end are cut
+/// short. Panics if verification panics, accepts anything but the true tag, or
+/// disagrees with `verify_hmac_checked`. Timing is not something a fuzzer can
+/// observe, so the constant-time property rests on `verify_hmac` itself.
+pub fn verify_hmac_target(input: &[u8]) {
+    let (key, rest) = split_prefixed(input);
+    let (tag, data) = split_prefixed(rest);
+
+    let expected = hmac_fixed(data, key);
+    let accepted = verify_hmac(data, tag, key);
+    assert_eq!(accepted, tag == expected.as_slice(), "verify_hmac disagrees with a direct comparison");
+    assert!(verify_hmac(data, &expected, key), "the true tag was rejected");
+
+    match verify_hmac_checked(data, tag, key) {
+        Ok(checked) => assert_eq!(checked, accepted),
+        Err(VerifyError::TagLength { got, .. }) => assert!(got != 32 && !accepted),
+        Err(err) => panic!("unexpected error {:?}", err),
+    }
+}
+
+// Splits off a field whose length is given by the first byte
+fn split_prefixed(input: &[u8]) -> (&[u8], &[u8]) {
+    match input.split_first() {
+        Some((&len, rest)) => rest.split_at((len as usize).min(rest.len())),
+        None => (&[], &[]),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fixed corpus from a xorshift generator, so the run is reproducible
+    fn corpus() -> Vec<Vec<u8>> {
+        let mut x = 0x9e37_79b9_7f4a_7c15_u64;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..500)
+            .map(|_| {
+                let len = (next() % 400) as usize;
+                (0..len).map(|_| next() as u8).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_hmac_target_corpus() {
+        for input in corpus() {
+            verify_hmac_target(&input);
+        }
+        for input in [&[][..], &[0], &[0xff], &[0xff, 0xff], &[0, 32], &[3, 1, 2]] {
+            verify_hmac_target(input);
+        }
+    }
+
+    #[test]
+    fn test_verify_hmac_target_with_true_tag() {
+        let key = b"fuzz key";
+        let data = b"fuzzed message";
+        let tag = hmac_fixed(data, key);
+        let input = [&[key.len() as u8][..], key, &[32], &tag, data].concat();
+        verify_hmac_target(&input);
+    }
+}
@@ -0,0 +1,76 @@
+//! Hex encoding for digests and tags.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub use crate::error::HexError;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+// Encodes bytes as lowercase hex, two characters per byte
+pub fn encode(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        hex.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        hex.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
+/// Decodes a hex string, accepting upper- and lowercase digits.
+///
+/// Fails with `HexError::OddLength` if the string has an odd number of
+/// characters, and with `HexError::InvalidChar` naming the first character
+/// that is not a hex digit.
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(HexError::OddLength { len: s.len() });
+    }
+
+    let mut bytes = Vec::with_capacity(s.len() / 2);
+    let mut chars = s.char_indices();
+    while let (Some(high), Some(low)) = (chars.next(), chars.next()) {
+        bytes.push((nibble(high)? << 4) | nibble(low)?);
+    }
+    Ok(bytes)
+}
+
+// Converts one hex digit, reporting its byte index if it isn't one
+fn nibble((index, ch): (usize, char)) -> Result<u8, HexError> {
+    match ch.to_digit(16) {
+        Some(value) => Ok(value as u8),
+        None => Err(HexError::InvalidChar { index, ch }),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hmac, verify_hmac};
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(&[]), "");
+        assert_eq!(encode(&[0x00, 0x0f, 0xa5, 0xff]), "000fa5ff");
+    }
+
+    #[test]
+    fn test_decode_round_trip() {
+        let tag = hmac(b"wire message", b"key");
+
+        assert_eq!(decode(&encode(&tag)).unwrap(), tag);
+        assert_eq!(decode(&encode(&tag).to_uppercase()).unwrap(), tag);
+        assert_eq!(decode("A5ff").unwrap(), [0xa5, 0xff]);
+        assert!(verify_hmac(b"wire message", &decode(&encode(&tag)).unwrap(), b"key"));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode("abc"), Err(HexError::OddLength { len: 3 }));
+        assert_eq!(decode("0g"), Err(HexError::InvalidChar { index: 1, ch: 'g' }));
+        assert_eq!(decode("00 1"), Err(HexError::InvalidChar { index: 2, ch: ' ' }));
+        // A multi-byte character is rejected by character, not split into bytes
+        assert_eq!(decode("é00"), Err(HexError::InvalidChar { index: 0, ch: 'é' }));
+    }
+}
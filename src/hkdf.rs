@@ -0,0 +1,93 @@
+//! HKDF-SHA256 key derivation (RFC 5869) on top of `hmac()`.
+
+use alloc::vec::Vec;
+
+use crate::error::KdfError;
+use crate::{hmac, Hmac};
+
+/// Largest output HKDF-Expand can produce: the block counter is a single byte.
+pub const MAX_OUTPUT_LEN: usize = 255 * 32;
+
+/// HKDF-Extract: returns the pseudorandom key `hmac(ikm, salt)`.
+///
+/// An empty `salt` is replaced by 32 zero bytes, as RFC 5869 specifies.
+#[must_use]
+pub fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    if salt.is_empty() {
+        return hmac(ikm, [0_u8; 32]);
+    }
+    hmac(ikm, salt)
+}
+
+/// HKDF-Expand: derives `length` bytes from `prk` bound to `info`.
+///
+/// Computes T(i) = HMAC(PRK, T(i-1) || info || i) for i = 1, 2, ... and
+/// returns the concatenation cut to `length`. Fails with
+/// `KdfError::OutputTooLong` if `length` exceeds `MAX_OUTPUT_LEN`.
+pub fn hkdf_expand(prk: &[u8], info: &[u8], length: usize) -> Result<Vec<u8>, KdfError> {
+    if length > MAX_OUTPUT_LEN {
+        return Err(KdfError::OutputTooLong { requested: length, max: MAX_OUTPUT_LEN });
+    }
+
+    let context = Hmac::new(prk);
+    let mut okm = Vec::with_capacity(length);
+    let mut previous: Vec<u8> = Vec::new();
+    let mut counter = 1_u8;
+    while okm.len() < length {
+        let mut mac = context.clone();
+        mac.update(&previous);
+        mac.update(info);
+        mac.update(&[counter]);
+        previous = mac.finalize();
+        okm.extend_from_slice(&previous);
+        counter = counter.wrapping_add(1);
+    }
+    okm.truncate(length);
+    Ok(okm)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode;
+
+    // RFC 5869 test case 1
+    #[test]
+    fn test_hkdf_basic() {
+        let ikm = [0x0b_u8; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+
+        let prk = hkdf_extract(&salt, &ikm);
+        assert_eq!(encode(&prk), "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5");
+
+        let okm = hkdf_expand(&prk, &info, 42).unwrap();
+        assert_eq!(
+            encode(&okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    // RFC 5869 test case 3: empty salt and empty info
+    #[test]
+    fn test_hkdf_empty_salt_and_info() {
+        let prk = hkdf_extract(b"", &[0x0b_u8; 22]);
+        assert_eq!(encode(&prk), "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04");
+
+        let okm = hkdf_expand(&prk, b"", 42).unwrap();
+        assert_eq!(
+            encode(&okm),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_expand_length_limit() {
+        assert_eq!(hkdf_expand(b"prk", b"", MAX_OUTPUT_LEN).unwrap().len(), MAX_OUTPUT_LEN);
+        assert_eq!(
+            hkdf_expand(b"prk", b"", MAX_OUTPUT_LEN + 1),
+            Err(KdfError::OutputTooLong { requested: MAX_OUTPUT_LEN + 1, max: MAX_OUTPUT_LEN })
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use alloc::vec::Vec;
+
+use crate::Hmac;
+
+/// Generates `length` bytes of keystream as `HMAC(key, nonce || counter)` blocks.
+///
+/// The counter is a big-endian `u64` starting at zero, and the 32-byte outputs
+/// are concatenated and cut to `length`, like CTR mode with HMAC as the PRF.
+///
+/// **This is not a vetted cipher.** It provides no integrity, falls apart if a
+/// nonce is ever reused under the same key, and has had no analysis as an
+/// encryption scheme. Use HKDF to derive key material and a real AEAD such as
+/// AES-GCM or ChaCha20-Poly1305 to encrypt.
+#[must_use]
+pub fn hmac_keystream(key: &[u8], nonce: &[u8], length: usize) -> Vec<u8> {
+    // Absorb the key once, then clone the keyed state for every block
+    let keyed = Hmac::new(key);
+    let mut keystream = Vec::with_capacity(length);
+    let mut counter: u64 = 0;
+    while keystream.len() < length {
+        let mut mac = keyed.clone();
+        mac.update(nonce);
+        mac.update(&counter.to_be_bytes());
+        let block: [u8; 32] = mac.finish();
+
+        let take = (length - keystream.len()).min(block.len());
+        keystream.extend_from_slice(&block[..take]);
+        counter += 1;
+    }
+    keystream
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac;
+
+    #[test]
+    fn test_keystream_deterministic() {
+        let a = hmac_keystream(b"key", b"nonce-1", 100);
+
+        assert_eq!(a.len(), 100);
+        assert_eq!(a, hmac_keystream(b"key", b"nonce-1", 100));
+        assert_eq!(a[..32], hmac([b"nonce-1".as_slice(), &0_u64.to_be_bytes()].concat(), b"key"));
+        assert_eq!(a[..50], hmac_keystream(b"key", b"nonce-1", 50));
+    }
+
+    #[test]
+    fn test_keystream_distinct_per_nonce() {
+        assert_ne!(hmac_keystream(b"key", b"nonce-1", 64), hmac_keystream(b"key", b"nonce-2", 64));
+        assert!(hmac_keystream(b"key", b"nonce-1", 0).is_empty());
+    }
+}
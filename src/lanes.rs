@@ -0,0 +1,64 @@
+use alloc::vec::Vec;
+
+use crate::utils::{pad, parse, sigma_256_0, sigma_256_1};
+use crate::constants::INITIAL_HASH;
+use crate::{compress_scheduled, state_to_bytes};
+
+/// Hashes `data`, expanding each message schedule `lanes` words at a time.
+///
+/// `lanes` must be 1, 2 or 4. Within a group, the `σ0` and addition terms of
+/// every word are independent and computed side by side; only the `σ1` term,
+/// which reads the word two positions back, is chained. The digest is the same
+/// for every lane count, so this is purely a throughput knob. Panics on any
+/// other lane count.
+#[must_use]
+pub fn hash_with_lanes(data: &[u8], lanes: usize) -> Vec<u8> {
+    assert!(matches!(lanes, 1 | 2 | 4), "lanes must be 1, 2 or 4, got {}", lanes);
+
+    let padded_message = pad(data).expect("padding always fills whole blocks");
+    let mut state = INITIAL_HASH;
+    for block in parse(&padded_message).expect("padding always fills whole blocks") {
+        compress_scheduled(&mut state, &schedule(&block, lanes));
+    }
+    state_to_bytes(&state).to_vec()
+}
+
+// Expands a block into the 64-word schedule, `lanes` words per step
+fn schedule(block: &[u32; 16], lanes: usize) -> [u32; 64] {
+    let mut w = [0_u32; 64];
+    w[..16].copy_from_slice(block);
+
+    let mut t = 16;
+    while t < 64 {
+        // These terms only read words from before the group
+        let mut partial = [0_u32; 4];
+        for (i, p) in partial[..lanes].iter_mut().enumerate() {
+            let j = t + i;
+            *p = w[j - 16].wrapping_add(sigma_256_0(w[j - 15])).wrapping_add(w[j - 7]);
+        }
+        // The σ1 term may read a word produced earlier in this group
+        for (i, p) in partial[..lanes].iter().enumerate() {
+            let j = t + i;
+            w[j] = p.wrapping_add(sigma_256_1(w[j - 2]));
+        }
+        t += lanes;
+    }
+    w
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_lane_counts_agree() {
+        for len in [0, 1, 55, 56, 64, 200, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            for lanes in [1, 2, 4] {
+                assert_eq!(hash_with_lanes(&data, lanes), hash(&data), "len {} lanes {}", len, lanes);
+            }
+        }
+    }
+}
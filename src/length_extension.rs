@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+
+use crate::error::{Error, HashError};
+use crate::utils::padding;
+use crate::Sha256;
+
+/// Forges `hash(secret || message || glue || append)` knowing only `hash(secret || message)`
+/// and its length. This is the length-extension attack, shown here for teaching.
+///
+/// Returns the glue padding followed by `append`, i.e. the bytes an attacker
+/// tacks onto the original message, together with the forged digest. It works
+/// because a SHA-256 digest is the full internal state after the padded input,
+/// so hashing can simply carry on from it. That is why `hash(secret || msg)`
+/// is not a MAC and `hmac()` should be used instead.
+///
+/// Fails with `HashError::InputTooLong` if `original_len`, or the extended
+/// message, would pass `MAX_INPUT_LEN` bytes.
+pub fn length_extension_forge(
+    original_digest: &[u8; 32],
+    original_len: u64,
+    append: &[u8],
+) -> Result<(Vec<u8>, [u8; 32]), Error> {
+    // The glue is the padding the original message got
+    let glue = padding(original_len)?;
+    let padded_len = original_len
+        .checked_add(glue.len() as u64)
+        .ok_or(HashError::InputTooLong { len: original_len })?;
+
+    // Recover the state words from the published digest
+    let mut state = [0_u32; 8];
+    for (word, chunk) in state.iter_mut().zip(original_digest.chunks(4)) {
+        *word = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    // Continue hashing as though the padded original had just been processed
+    let mut hasher = Sha256::from_state(crate::Sha256State { state, total_len: padded_len })?;
+    hasher.try_update(append)?;
+    let forged = hasher.finish();
+
+    let mut extension = glue;
+    extension.extend_from_slice(append);
+    Ok((extension, forged))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_length_extension_forge() {
+        let secret = b"server-side secret";
+        let message = b"user=bernie&role=guest";
+        let append = b"&role=admin";
+
+        let original: [u8; 32] = hash([secret.as_slice(), message].concat()).try_into().unwrap();
+        let original_len = (secret.len() + message.len()) as u64;
+        let (extension, forged) = length_extension_forge(&original, original_len, append).unwrap();
+
+        // The attacker never saw the secret, yet the forgery matches
+        let mut full_message = [secret.as_slice(), message].concat();
+        full_message.extend_from_slice(&extension);
+
+        assert_eq!(forged.to_vec(), hash(&full_message));
+        assert_eq!(extension[0], 0x80);
+        assert!(extension.ends_with(append));
+        assert_eq!((original_len as usize + extension.len() - append.len()) % 64, 0);
+    }
+
+    #[test]
+    fn test_length_extension_forge_rejects_oversized_length() {
+        let digest = [0_u8; 32];
+        let too_long = u64::MAX / 8 + 1;
+        assert_eq!(
+            length_extension_forge(&digest, too_long, b"x"),
+            Err(Error::Hash(HashError::InputTooLong { len: too_long }))
+        );
+        // The padded original fits, but appending pushes it over
+        assert!(length_extension_forge(&digest, u64::MAX / 8 - 100, &[0; 200]).is_err());
+    }
+}
@@ -1,138 +1,1112 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(non_snake_case)]
+extern crate alloc;
+
 mod utils;
 mod constants;
+mod error;
+mod algorithm;
+#[cfg(feature = "std")]
+mod backoff;
+mod batch;
+mod build_hasher;
+mod checkpointing;
+mod const_hash;
+pub mod hex;
+pub mod hkdf;
+mod keystream;
+#[cfg(feature = "portable-simd")]
+mod lanes;
+mod length_extension;
+pub mod otp;
+mod output;
+#[cfg(feature = "std")]
+mod path;
+pub mod pbkdf2;
+mod scram;
+mod sequenced;
+mod sha224;
+#[cfg(feature = "sha512")]
+mod sha512;
+#[cfg(feature = "std")]
+mod stream;
+mod subkeys;
+mod tagged;
+#[cfg(feature = "test-utils")]
+mod test_utils;
+#[cfg(feature = "serde")]
+mod canonical;
+#[cfg(feature = "serde")]
+mod serialized;
+#[cfg(feature = "digest")]
+mod dyn_digest;
+#[cfg(feature = "digest")]
+mod digest_traits;
+#[cfg(feature = "debug-tools")]
+mod debug;
+#[cfg(feature = "rand")]
+mod challenge;
+#[cfg(feature = "parallel")]
+mod tree;
+#[cfg(any(feature = "fuzz", test))]
+mod fuzz;
+
+use alloc::{format, string::String, vec::Vec};
+use zeroize::{Zeroize, Zeroizing};
+use crate::utils::{bit_length, pad_bits, parse, parse_block, ct_equal, sigma_256_1, sigma_256_0, Sigma_256_0, Sigma_256_1, ch, maj};
+use crate::constants::{PRIME_CUBES, BLOCKSIZE};
 
-use crate::utils::{pad, parse, sigma_256_1, sigma_256_0, Sigma_256_0, Sigma_256_1, ch, maj};
-use crate::constants::{INITIAL_HASH, PRIME_CUBES, BLOCKSIZE};
+pub use crate::constants::{BLOCK_SIZE, INITIAL_HASH, MAX_INPUT_LEN, OUTPUT_SIZE};
+pub use crate::error::{Error, HashError, HexError, KdfError, VerifyError};
+pub use crate::utils::padded_len;
+pub use crate::algorithm::{supported_algorithms, Algorithm};
+#[cfg(feature = "std")]
+pub use crate::backoff::HmacVerifierWithBackoff;
+pub use crate::batch::{chunk_digests_dedup, hash_all_into, hash_batch_same_length, MultiSha256};
+pub use crate::build_hasher::{Sha256BuildHasher, Sha256Hasher};
+pub use crate::checkpointing::CheckpointingHasher;
+pub use crate::const_hash::hash_const;
+pub use crate::keystream::hmac_keystream;
+#[cfg(feature = "portable-simd")]
+pub use crate::lanes::hash_with_lanes;
+pub use crate::length_extension::length_extension_forge;
+pub use crate::output::{Digest, Tag};
+#[cfg(feature = "std")]
+pub use crate::path::hash_path;
+pub use crate::scram::{
+    scram_client_key, scram_client_proof, scram_client_signature, scram_server_key, scram_server_signature,
+    scram_stored_key,
+};
+pub use crate::sequenced::SequencedHmac;
+pub use crate::sha224::{hash224, hmac224};
+#[cfg(feature = "sha512")]
+pub use crate::sha512::{hash512, hash512_256, hmac512};
+#[cfg(feature = "std")]
+pub use crate::stream::{
+    hash_channel, hash_file, hash_file_digest, hash_reader, hash_reader_exact, hmac_file, hmac_file_digest, LimitedHashingWriter,
+};
+pub use crate::subkeys::derive_subkeys;
+pub use crate::tagged::{tagged_mac, verify_tagged_mac, MAC_VERSION_SHA256};
+#[cfg(feature = "test-utils")]
+pub use crate::test_utils::assert_streaming_consistency;
+#[cfg(feature = "serde")]
+pub use crate::canonical::hmac_value;
+#[cfg(feature = "serde")]
+pub use crate::serialized::hash_serialized;
+#[cfg(feature = "debug-tools")]
+pub use crate::debug::hmac_debug;
+#[cfg(feature = "rand")]
+pub use crate::challenge::{make_challenge, verify_response};
+#[cfg(feature = "parallel")]
+pub use crate::tree::hash_tree;
+#[cfg(feature = "fuzz")]
+pub use crate::fuzz::verify_hmac_target;
 
 
-// This function normalizes the key length to assure it contains exactly BLOCKSIZE many bytes
-fn normalize(key: &[u8]) -> Vec<u8> {
-    let mut normalized_key: Vec<u8> = Vec::with_capacity(64);
+// This function normalizes the key length to assure it contains exactly BLOCKSIZE many bytes.
+// The result lives on the stack, so no key material is copied to the heap
+fn normalize(key: &[u8]) -> [u8; BLOCKSIZE] {
+    // Zero bytes past the end of the key double as its padding
+    let mut normalized_key = [0_u8; BLOCKSIZE];
     if key.len() > BLOCKSIZE {
         // If the key length is greater than the blocklength, we hash it first
-        let hashed_key = hash(key);
-        normalized_key.extend(hashed_key);
+        // The hasher's buffer holds raw key bytes, so it is wiped when dropped
+        let mut hasher = Zeroizing::new(Sha256::new());
+        hasher.update(key);
+        let digest = Zeroizing::new(state_to_bytes(&hasher.finish_in_place()));
+        normalized_key[..32].copy_from_slice(&*digest);
     } else {
-        normalized_key.extend_from_slice(key);
+        normalized_key[..key.len()].copy_from_slice(key);
     }
 
-    // Add padding to the key to assure it has a total of blocksize many bytes
-    let pad = vec![0_u8; BLOCKSIZE - normalized_key.len()];
-    normalized_key.extend(pad);
     normalized_key
 }
 
-pub fn hash(data: &[u8]) -> Vec<u8> {
-    // Preprocess
-    let padded_message = pad(data).unwrap();
-    let message_blocks = parse(&padded_message);
-    let mut hash_value: [u32; 8] = INITIAL_HASH;
+// Runs the SHA-256 compression function on a single parsed block, updating the state in place.
+// The message schedule is computed on the fly in a rolling 16-word window, since
+// round t only reads words t - 2, t - 7, t - 15 and t - 16. Written with while
+// loops so that it is a const fn and hash_const can share it
+pub(crate) const fn compress(state: &mut [u32; 8], block: &[u32; 16]) {
+    let mut w = *block;
 
-    // Process each message block
-    let num_blocks = message_blocks.len();
-    for i in 0..num_blocks {
-        // Initialize the message schedule
-        let mut message_schedule: [u32; 64] = [0_u32; 64];
-        for t in 0..64 {
-            if t < 16 {
-                message_schedule[t] = message_blocks[i][t];
-            } else {
-                message_schedule[t] = sigma_256_1(message_schedule[t - 2])
-                                                .wrapping_add(message_schedule[t - 7])
-                                                .wrapping_add(sigma_256_0(message_schedule[t - 15]))
-                                                .wrapping_add(message_schedule[t - 16]);
+    // Initialize the eight working variables with the last hash value
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    let mut t = 0;
+    while t < 64 {
+        // Past the first 16 rounds, overwrite the word from t - 16 with word t
+        if t >= 16 {
+            w[t & 15] = sigma_256_1(w[(t - 2) & 15])
+                            .wrapping_add(w[(t - 7) & 15])
+                            .wrapping_add(sigma_256_0(w[(t - 15) & 15]))
+                            .wrapping_add(w[t & 15]);
+        }
+
+        let t1 = h.wrapping_add(Sigma_256_1(e))
+                  .wrapping_add(ch(e, f, g))
+                  .wrapping_add(PRIME_CUBES[t])
+                  .wrapping_add(w[t & 15]);
+        let t2 = Sigma_256_0(a).wrapping_add(maj(a, b, c));
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+        t += 1;
+    }
+
+    // Update the hash value
+    let temp_values = [a, b, c, d, e, f, g, h];
+    let mut i = 0;
+    while i < 8 {
+        state[i] = state[i].wrapping_add(temp_values[i]);
+        i += 1;
+    }
+}
+
+/// Runs one SHA-256 compression of `block` on `state` and returns the new state.
+///
+/// This is the raw primitive, with no padding or length handling: `block` is
+/// 16 big-endian message words and `state` any chaining value, such as
+/// `INITIAL_HASH` or a custom IV.
+#[must_use]
+pub fn compress_block(mut state: [u32; 8], block: [u32; 16]) -> [u32; 8] {
+    compress(&mut state, &block);
+    state
+}
+
+// Runs the 64 rounds over an already expanded message schedule, for hash_with_lanes
+// and as the full-schedule reference in tests
+#[cfg(any(feature = "portable-simd", test))]
+pub(crate) fn compress_scheduled(state: &mut [u32; 8], message_schedule: &[u32; 64]) {
+    // Initialize the eight working variables with the last hash value
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+    let mut f = state[5];
+    let mut g = state[6];
+    let mut h = state[7];
+
+    // Compute the two temporary words and update the working variables
+    let mut t1: u32;
+    let mut t2: u32;
+    for t in 0..64 {
+        t1 = h.wrapping_add(Sigma_256_1(e))
+                .wrapping_add(ch(e, f, g))
+                .wrapping_add(PRIME_CUBES[t])
+                .wrapping_add(message_schedule[t]);
+
+        t2 = Sigma_256_0(a).wrapping_add(maj(a, b, c));
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    // Update the hash value
+    let temp_values = [a, b, c, d, e, f, g, h];
+    for (i, temp_value) in temp_values.iter().enumerate() {
+        state[i] = temp_value.wrapping_add(state[i]);
+    }
+}
+
+// Serializes the eight state words into the 32 digest bytes
+pub(crate) fn state_to_bytes(state: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0_u8; 32];
+    for (chunk, word) in bytes.chunks_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+#[must_use]
+pub fn hash(data: impl AsRef<[u8]>) -> Vec<u8> {
+    hash_fixed(data.as_ref()).to_vec()
+}
+
+/// Hashes `data` into a stack array, without the heap allocation `hash()` makes.
+#[must_use]
+pub fn hash_fixed(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish()
+}
+
+// Copies a finished digest or tag into the front of a caller buffer
+fn write_output(result: &[u8; 32], out: &mut [u8]) -> Result<(), Error> {
+    if out.len() < result.len() {
+        return Err(Error::BufferTooSmall { needed: result.len(), got: out.len() });
+    }
+    out[..result.len()].copy_from_slice(result);
+    Ok(())
+}
+
+// Incremental SHA-256 hasher. Complete blocks are compressed as data arrives
+// and only the final partial block is kept around until finalize, which pads it
+// in place. Finalizing therefore uses the same fixed memory for any input length.
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    buffer: [u8; BLOCKSIZE],
+    buffer_len: usize,
+    total_len: u64,
+}
+
+impl Sha256 {
+    pub const OUTPUT_SIZE: usize = OUTPUT_SIZE;
+    pub const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    pub fn new() -> Self {
+        Sha256 {
+            state: INITIAL_HASH,
+            buffer: [0_u8; BLOCKSIZE],
+            buffer_len: 0,
+            total_len: 0,
+        }
+    }
+
+    // Resumes hashing from a chaining value after total_len bytes (a whole number of blocks)
+    pub(crate) fn resume(state: [u32; 8], total_len: u64) -> Self {
+        Sha256 {
+            state,
+            buffer: [0_u8; BLOCKSIZE],
+            buffer_len: 0,
+            total_len,
+        }
+    }
+
+    // Restores the initial state so the hasher can be reused for an unrelated message
+    pub fn reset(&mut self) {
+        *self = Sha256::new();
+    }
+
+    /// Feeds `data` into the hash.
+    ///
+    /// Panics if the total input would exceed `MAX_INPUT_LEN` bytes, the most
+    /// SHA-256 can encode in its length field. Use `try_update` to get that as
+    /// an error instead.
+    pub fn update(&mut self, data: &[u8]) {
+        if let Err(err) = self.try_update(data) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Feeds `data` like `update`, or fails with `HashError::InputTooLong` if the
+    /// total input would exceed `MAX_INPUT_LEN` bytes. On error nothing is absorbed.
+    pub fn try_update(&mut self, mut data: &[u8]) -> Result<(), HashError> {
+        self.total_len = self.checked_total_len(data.len())?;
+
+        // Top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (BLOCKSIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < BLOCKSIZE {
+                return Ok(());
             }
+            compress(&mut self.state, &parse_block(&self.buffer));
+            self.buffer_len = 0;
         }
 
-        // Initialize the eight working variables with the last hash value
-        let mut a = hash_value[0];
-        let mut b = hash_value[1];
-        let mut c = hash_value[2];
-        let mut d = hash_value[3];
-        let mut e = hash_value[4];
-        let mut f = hash_value[5];
-        let mut g = hash_value[6];
-        let mut h = hash_value[7];
+        // Compress whole blocks straight out of the input
+        let mut blocks = data.chunks_exact(BLOCKSIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, &parse_block(block));
+        }
 
-        // Compute the two temporary words and update the working variables
-        let mut t1: u32;
-        let mut t2: u32;
-        for t in 0..64 {
-            t1 = h.wrapping_add(Sigma_256_1(e))
-                    .wrapping_add(ch(e, f, g))
-                    .wrapping_add(PRIME_CUBES[t])
-                    .wrapping_add(message_schedule[t]);
+        // Keep the remainder for the next call
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+        Ok(())
+    }
+
+    // Returns the byte count after absorbing more bytes, if it stays within MAX_INPUT_LEN
+    pub(crate) fn checked_total_len(&self, more: usize) -> Result<u64, HashError> {
+        self.total_len
+            .checked_add(more as u64)
+            .filter(|&len| len <= MAX_INPUT_LEN)
+            .ok_or(HashError::InputTooLong { len: self.total_len.saturating_add(more as u64) })
+    }
+
+    #[must_use]
+    pub fn finalize(self) -> Vec<u8> {
+        self.finish().to_vec()
+    }
+
+    // Writes the digest into the first 32 bytes of out, leaving any remaining bytes untouched
+    pub fn finalize_into(self, out: &mut [u8]) -> Result<(), Error> {
+        write_output(&self.finish(), out)
+    }
+
+    // Returns the digest along with the total number of bytes that were fed
+    #[must_use]
+    pub fn finalize_counted(self) -> (Vec<u8>, u64) {
+        let total_len = self.total_len;
+        (self.finalize(), total_len)
+    }
+
+    /// Returns the digest and whether it matches `expected`, if one was given.
+    ///
+    /// With `Some(expected)` the digest is compared in constant time; with
+    /// `None` there is nothing to check and the flag is `true`.
+    #[must_use]
+    pub fn finalize_checked(self, expected: Option<&[u8]>) -> (Vec<u8>, bool) {
+        let digest = self.finish();
+        let matches = expected.is_none_or(|expected| ct_equal(&digest, expected));
+        (digest.to_vec(), matches)
+    }
+
+    /// Captures the current position so a speculative `update` can be undone with `restore`.
+    ///
+    /// The checkpoint holds the eight state words, the byte count and the unprocessed
+    /// tail. Since that tail never exceeds one 64-byte block, a checkpoint costs
+    /// the same fixed-size copy whether or not it falls on a block boundary.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.clone())
+    }
+
+    // Rolls the hasher back to an earlier checkpoint, discarding everything fed since
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        *self = checkpoint.0;
+    }
 
-            t2 = Sigma_256_0(a).wrapping_add(maj(a, b, c));
+    /// Exports the chaining value and byte count, for research and teaching.
+    ///
+    /// **This exposes an attack primitive.** Resuming from the state of a
+    /// secret-prefixed hash is exactly the SHA-256 length extension attack; use
+    /// it to demonstrate that, never in a protocol. Only block boundaries can be
+    /// exported, so this fails with `Error::PartialBlock` while bytes of an
+    /// unfinished block are buffered.
+    pub fn export_state(&self) -> Result<Sha256State, Error> {
+        if self.buffer_len != 0 {
+            return Err(Error::PartialBlock { buffered: self.buffer_len });
+        }
+        Ok(Sha256State { state: self.state, total_len: self.total_len })
+    }
 
-            h = g;
-            g = f;
-            f = e;
-            e = d.wrapping_add(t1);
-            d = c;
-            c = b;
-            b = a;
-            a = t1.wrapping_add(t2);
+    /// Resumes hashing from an exported state, as if its `total_len` bytes had been fed.
+    ///
+    /// The state is taken as is. A `total_len` that isn't the length actually
+    /// absorbed only changes the length field written at `finalize`, but one past
+    /// `MAX_INPUT_LEN` is rejected with `HashError::InputTooLong`. Like
+    /// `export_state`, this is meant for research and testing.
+    pub fn from_state(state: Sha256State) -> Result<Self, HashError> {
+        if state.total_len > MAX_INPUT_LEN {
+            return Err(HashError::InputTooLong { len: state.total_len });
         }
+        Ok(Self::resume(state.state, state.total_len))
+    }
+
+    /// Returns the digest of everything fed so far without disturbing this hasher.
+    ///
+    /// The padding is applied to a copy of the state, so `update` and `finalize`
+    /// carry on as if the snapshot had never been taken.
+    #[must_use]
+    pub fn snapshot_digest(&self) -> [u8; 32] {
+        self.clone().finish()
+    }
+
+    // Applies the padding to the buffered tail and returns the final digest
+    fn finish(self) -> [u8; 32] {
+        state_to_bytes(&self.finish_words())
+    }
 
-        // Update the hash value
-        let temp_values = [a, b, c, d, e, f, g, h];
-        for (i, temp_value) in temp_values.iter().enumerate() {
-            hash_value[i] = temp_value.wrapping_add(hash_value[i]);
+    // Applies the padding to the buffered tail and returns the final state words
+    fn finish_words(mut self) -> [u32; 8] {
+        self.finish_in_place()
+    }
+
+    // Pads and compresses the tail in place, leaving the final state in self. Keyed
+    // hashers finish this way so that no unwiped copy of their state is made
+    fn finish_in_place(&mut self) -> [u32; 8] {
+        // update and from_state keep total_len within MAX_INPUT_LEN, so this can't fail
+        let bit_len = bit_length(self.total_len).expect("total_len exceeds MAX_INPUT_LEN");
+
+        // Append a '1' bit followed by zeros
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+
+        // If there is no room left for the 64-bit length, it spills into another block
+        if self.buffer_len >= BLOCKSIZE - 8 {
+            compress(&mut self.state, &parse_block(&self.buffer));
+            self.buffer.fill(0);
         }
+
+        self.buffer[BLOCKSIZE - 8..].copy_from_slice(&bit_len.to_be_bytes());
+        compress(&mut self.state, &parse_block(&self.buffer));
+
+        self.state
+    }
+}
+
+// The exported midstate of a Sha256 at a block boundary: the eight chaining words
+// and the number of bytes absorbed so far
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sha256State {
+    pub state: [u32; 8],
+    pub total_len: u64,
+}
+
+// A saved position in a Sha256 stream, created by Sha256::checkpoint
+#[derive(Clone)]
+pub struct Checkpoint(Sha256);
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Zeroize for Sha256 {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.buffer.zeroize();
+        self.buffer_len.zeroize();
+        self.total_len.zeroize();
+    }
+}
+
+/// Hashes input that the caller has already padded, skipping `pad()` entirely.
+///
+/// The blocks are compressed as given, so `hash_prepadded(&pad(data))` equals
+/// `hash(data)`. Meant for conformance testing with externally padded blocks.
+/// Input that is not a whole number of 64-byte blocks is rejected.
+pub fn hash_prepadded(padded: &[u8]) -> Result<Vec<u8>, Error> {
+    let message_blocks = parse(padded)?;
+    let mut hash_value: [u32; 8] = INITIAL_HASH;
+    for block in &message_blocks {
+        compress(&mut hash_value, block);
+    }
+    Ok(state_to_bytes(&hash_value).to_vec())
+}
+
+/// Hashes the first `bit_len` bits of `data`, for messages that are not whole bytes.
+///
+/// Bits past `bit_len` in the final partial byte are ignored and the length
+/// field encodes `bit_len`, so this reproduces the bit-oriented NIST vectors.
+/// `hash_bits(data, data.len() * 8)` equals `hash(data)`.
+///
+/// Panics if `bit_len` is greater than `data.len() * 8`.
+pub fn hash_bits(data: &[u8], bit_len: usize) -> Vec<u8> {
+    assert!(bit_len <= data.len() * 8, "bit_len {} exceeds the {} bits of data", bit_len, data.len() * 8);
+
+    let mut hash_value: [u32; 8] = INITIAL_HASH;
+    for block in &parse(&pad_bits(data, bit_len)).expect("padding always fills whole blocks") {
+        compress(&mut hash_value, block);
     }
+    state_to_bytes(&hash_value).to_vec()
+}
+
+/// Hashes a buffer of `MaybeUninit<u8>`, such as one filled in by C through an
+/// out-parameter, without first copying it into initialized memory.
+///
+/// # Safety
+///
+/// Every byte of `data` must have been initialized. Hashing a buffer with any
+/// uninitialized byte is undefined behavior, even if the digest is never used.
+#[must_use]
+pub unsafe fn hash_assume_init(data: &[core::mem::MaybeUninit<u8>]) -> Vec<u8> {
+    // SAFETY: MaybeUninit<u8> has the same layout as u8, and the caller guarantees
+    // that all data.len() bytes are initialized
+    let bytes = unsafe { core::slice::from_raw_parts(data.as_ptr() as *const u8, data.len()) };
+    hash(bytes)
+}
+
+/// Hashes an ordered pair of byte strings unambiguously.
+///
+/// The input to SHA-256 is `len(a) || a || len(b) || b`, each length being the
+/// byte count as a big-endian u64. The prefixes make every split distinct, so
+/// `hash_pair(b"ab", b"")` and `hash_pair(b"", b"ab")` differ.
+pub fn hash_pair(a: &[u8], b: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&(a.len() as u64).to_be_bytes());
+    hasher.update(a);
+    hasher.update(&(b.len() as u64).to_be_bytes());
+    hasher.update(b);
+    hasher.finish()
+}
 
-    // Construct the final hash by concatenating the bytes of hash_value
-    let mut final_hash: Vec<u8> = Vec::with_capacity(32);
-    for word in hash_value {
-        final_hash.extend(word.to_be_bytes());
+/// Hashes the concatenation of `chunks` without copying them into one buffer.
+///
+/// Each slice is fed to the streaming hasher in turn, and the length field
+/// counts the bytes of every chunk, so the digest equals `hash(chunks.concat())`.
+#[must_use]
+pub fn hash_many(chunks: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk);
     }
+    hasher.finalize()
+}
+
+// Hashes data and returns the digest together with the input length in bytes
+pub fn hash_counted(data: &[u8]) -> ([u8; 32], u64) {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    (hasher.finish(), data.len() as u64)
+}
 
-    final_hash
+// Checks data against an expected digest in constant time. An expected value of
+// the wrong length simply fails to match
+#[must_use]
+pub fn hash_verify(data: &[u8], expected: &[u8]) -> bool {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    let computed = hasher.finish();
+
+    ct_equal(&computed, expected)
+}
+
+/// Returns a content-addressed storage path for `data`, such as `ab/cd/abcd1234...`.
+///
+/// The path is the lowercase hex of `hash(data)`, sharded into two directory
+/// levels by its first and second bytes, followed by the full 64-character hex
+/// digest as the file name. That caps each directory at 256 entries, and every
+/// file still names its whole digest. Hex digits are safe on every filesystem.
+#[must_use]
+pub fn content_address(data: &[u8]) -> String {
+    let hex = hex::encode(&hash_fixed(data));
+    format!("{}/{}/{}", &hex[..2], &hex[2..4], hex)
+}
+
+// Returns the final SHA-256 state as eight words instead of bytes. The digest is
+// these words serialized big-endian
+pub fn hash_words(data: &[u8]) -> [u32; 8] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finish_words()
+}
+
+// Hashes a sequence of words as their big-endian bytes, without the caller
+// building a byte buffer first
+pub fn hash_u32_words(words: &[u32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for word in words {
+        hasher.update(&word.to_be_bytes());
+    }
+    hasher.finish()
 }
 
-pub fn hmac(data: &[u8], key: &[u8]) -> Vec<u8> {
-    // Normalize the key
-    let normalized_key = normalize(key);
+// Derives the inner and outer key blocks by XORing the normalized key with ipad and opad
+fn key_pads(key: &[u8]) -> ([u8; BLOCKSIZE], [u8; BLOCKSIZE]) {
+    let normalized_key = Zeroizing::new(normalize(key));
+    xor_pads(&*normalized_key)
+}
 
+// XORs a key of at most BLOCKSIZE bytes into ipad and opad. Missing bytes count as zero
+fn xor_pads(normalized_key: &[u8]) -> ([u8; BLOCKSIZE], [u8; BLOCKSIZE]) {
     // Initialize values for inner padding and outer padding
-    let ipad = vec![0x36; BLOCKSIZE];
-    let opad = vec![0x5c; BLOCKSIZE];
+    let mut inner_key = [0x36; BLOCKSIZE];
+    let mut outer_key = [0x5c; BLOCKSIZE];
 
     // XOR the normalized key with ipad and opad
-    let inner_key: Vec<u8> = normalized_key.iter().zip(ipad.iter()).map(|(&k, &i)| k ^ i).collect();
-    let outer_key: Vec<u8> = normalized_key.iter().zip(opad.iter()).map(|(&k, &o)| k ^ o).collect();
-
-    // Append the data to the inner key and hash
-    let inner_hash = {
-        let mut inner = inner_key.clone();
-        inner.extend_from_slice(data);
-        hash(&inner)
-    };
+    for (i, &k) in normalized_key.iter().enumerate() {
+        inner_key[i] ^= k;
+        outer_key[i] ^= k;
+    }
 
-    // Append the inner hash to the outer key and hash
-    let outer_hash = {
-        let mut outer = outer_key;
-        outer.extend_from_slice(&inner_hash);
-        hash(&outer)
-    };
+    (inner_key, outer_key)
+}
+
+// Longest message whose padding still fits in the block after the key block
+const SHORT_MESSAGE_LEN: usize = BLOCKSIZE - 9;
+
+// Compresses the last block of a message whose first prefix_len bytes (a whole
+// number of blocks) are already in state, given the remaining tail of at most
+// SHORT_MESSAGE_LEN bytes
+fn compress_final_block(state: &mut [u32; 8], tail: &[u8], prefix_len: usize) {
+    let mut block = [0_u8; BLOCKSIZE];
+    block[..tail.len()].copy_from_slice(tail);
+    block[tail.len()] = 0x80;
+    block[BLOCKSIZE - 8..].copy_from_slice(&(((prefix_len + tail.len()) * 8) as u64).to_be_bytes());
+    compress(state, &parse_block(&block));
+}
 
-    outer_hash
+// HMAC for messages of at most SHORT_MESSAGE_LEN bytes. Both the inner and the outer
+// hash are exactly two blocks, so everything is done with fixed buffers on the stack
+fn hmac_short(data: &[u8], key: &[u8]) -> [u8; 32] {
+    let (inner_key, outer_key) = key_pads(key);
+    let (inner_key, outer_key) = (Zeroizing::new(inner_key), Zeroizing::new(outer_key));
+
+    let mut inner = Zeroizing::new(INITIAL_HASH);
+    compress(&mut inner, &parse_block(&*inner_key));
+    compress_final_block(&mut inner, data, BLOCKSIZE);
+
+    let mut outer = Zeroizing::new(INITIAL_HASH);
+    compress(&mut outer, &parse_block(&*outer_key));
+    compress_final_block(&mut outer, &state_to_bytes(&inner), BLOCKSIZE);
+
+    state_to_bytes(&outer)
+}
+
+#[must_use]
+pub fn hmac(data: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> Vec<u8> {
+    hmac_fixed(data.as_ref(), key.as_ref()).to_vec()
+}
+
+/// Computes the HMAC of `data` into a stack array, without the final `Vec` `hmac()` returns.
+#[must_use]
+pub fn hmac_fixed(data: &[u8], key: &[u8]) -> [u8; 32] {
+    // Short messages take the allocation-free path
+    if data.len() <= SHORT_MESSAGE_LEN {
+        return hmac_short(data, key);
+    }
+
+    // Stream the inner key block and then the data through the inner hash, so the
+    // message is never copied behind the key. Hmac wipes its keyed states on drop
+    let mut mac = Hmac::new(key);
+    mac.update(data);
+    mac.finish()
+}
+
+// Returns the final HMAC state (the outer hash) as eight words instead of bytes,
+// for callers that keep computing on words. The tag is these words serialized big-endian
+pub fn hmac_words(data: &[u8], key: &[u8]) -> [u32; 8] {
+    let mut mac = Hmac::new(key);
+    mac.update(data);
+    mac.finish_words()
+}
+
+/// Computes the HMAC of the concatenation of `data_slices` without gathering them.
+///
+/// Each slice is streamed through the inner hash after the key block, so a header
+/// and body held in separate buffers can be MACed without a copy. The result
+/// equals `hmac(&data_slices.concat(), key)`.
+#[must_use]
+pub fn hmac_vectored(data_slices: &[&[u8]], key: &[u8]) -> Vec<u8> {
+    let (inner_key, outer_key) = key_pads(key);
+    let (inner_key, outer_key) = (Zeroizing::new(inner_key), Zeroizing::new(outer_key));
+
+    let mut inner = Zeroizing::new(Sha256::new());
+    inner.update(&*inner_key);
+    for slice in data_slices {
+        inner.update(slice);
+    }
+    let inner_hash = Zeroizing::new(state_to_bytes(&inner.finish_in_place()));
+
+    let mut outer = Zeroizing::new(Sha256::new());
+    outer.update(&*outer_key);
+    outer.update(&*inner_hash);
+    state_to_bytes(&outer.finish_in_place()).to_vec()
+}
+
+// Incremental HMAC-SHA256. The key blocks are absorbed once up front, so the
+// context can be cloned to MAC many messages under the same key.
+#[derive(Clone)]
+pub struct Hmac {
+    inner: Sha256,
+    outer: Sha256,
+    buffered: Vec<u8>,
+    framing: Option<Framing>,
+}
+
+// Progress through the AAD and data sections fed with update_aad and update_data
+#[derive(Clone, Copy)]
+struct Framing {
+    aad_len: u64,
+    data_len: u64,
+    in_data: bool,
+}
+
+impl Hmac {
+    pub fn new(key: &[u8]) -> Self {
+        Self::from_pads(key_pads(key))
+    }
+
+    /// Builds the context from a key that the caller already reduced with `hash()`.
+    ///
+    /// The caller asserts that `digest == hash(original_key)` for a key longer than
+    /// the block size. The 32 bytes are used as the key directly, skipping
+    /// `normalize()`, and the tags equal `Hmac::new(original_key)`.
+    pub fn from_prehashed_key(digest: &[u8; 32]) -> Self {
+        Self::from_pads(xor_pads(digest))
+    }
+
+    // Absorbs a fixed prefix once, so every later tag covers prefix || message
+    pub fn with_key_and_prefix(key: &[u8], prefix: &[u8]) -> Self {
+        let mut mac = Self::new(key);
+        mac.update(prefix);
+        mac
+    }
+
+    // Returns the tag of everything fed so far followed by data, leaving this context untouched
+    #[must_use]
+    pub fn sign(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = self.clone();
+        mac.update(data);
+        mac.finalize()
+    }
+
+    fn from_pads((mut inner_key, mut outer_key): ([u8; BLOCKSIZE], [u8; BLOCKSIZE])) -> Self {
+        let mut inner = Sha256::new();
+        inner.update(&inner_key);
+        let mut outer = Sha256::new();
+        outer.update(&outer_key);
+        inner_key.zeroize();
+        outer_key.zeroize();
+        Hmac { inner, outer, buffered: Vec::new(), framing: None }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    // Feeds data, or fails with InputTooLong once the inner hash would pass MAX_INPUT_LEN
+    pub fn try_update(&mut self, data: &[u8]) -> Result<(), HashError> {
+        self.inner.try_update(data)
+    }
+
+    /// Feeds associated data, authenticated under its own length frame.
+    ///
+    /// With `update_aad` and `update_data` the tag covers
+    /// `aad || len(aad) || data || len(data)`, each length the total byte count
+    /// of its section as a big-endian `u64`. The frames make it impossible to
+    /// move bytes between the two sections without changing the tag, however
+    /// the calls are chunked. All AAD must come before any data, and these
+    /// calls should not be mixed with plain `update` on the same context.
+    ///
+    /// Panics if called after `update_data`.
+    pub fn update_aad(&mut self, aad: &[u8]) {
+        let framing = self.framing.get_or_insert(Framing { aad_len: 0, data_len: 0, in_data: false });
+        assert!(!framing.in_data, "update_aad called after update_data");
+        framing.aad_len += aad.len() as u64;
+        self.inner.update(aad);
+    }
+
+    // Feeds message data after the AAD. The first call closes the AAD section
+    pub fn update_data(&mut self, data: &[u8]) {
+        let framing = self.framing.get_or_insert(Framing { aad_len: 0, data_len: 0, in_data: false });
+        if !framing.in_data {
+            framing.in_data = true;
+            self.inner.update(&framing.aad_len.to_be_bytes());
+        }
+        framing.data_len += data.len() as u64;
+        self.inner.update(data);
+    }
+
+    /// Feeds `data` like `update` and also keeps a copy for `finalize_verify`.
+    ///
+    /// Everything passed here is held in memory until the context is finalized,
+    /// so the cost grows with the message. Only use it for messages that fit.
+    pub fn update_and_buffer(&mut self, data: &[u8]) {
+        self.inner.update(data);
+        self.buffered.extend_from_slice(data);
+    }
+
+    #[must_use]
+    pub fn finalize(self) -> Vec<u8> {
+        self.finish().to_vec()
+    }
+
+    /// Returns the data fed through `update_and_buffer` only if the tag matches `expected`.
+    ///
+    /// The tag covers everything fed to the context, buffered or not. Callers
+    /// only get the data back once it is authenticated, and `None` otherwise.
+    #[must_use]
+    pub fn finalize_verify(mut self, expected: &[u8]) -> Option<Vec<u8>> {
+        let buffered = core::mem::take(&mut self.buffered);
+        if ct_equal(&self.finish(), expected) {
+            Some(buffered)
+        } else {
+            None
+        }
+    }
+
+    // Writes the tag into the first 32 bytes of out, leaving any remaining bytes untouched
+    pub fn finalize_into(self, out: &mut [u8]) -> Result<(), Error> {
+        write_output(&self.finish(), out)
+    }
+
+    // Produces the tag without touching the heap
+    fn finish(self) -> [u8; 32] {
+        state_to_bytes(&self.finish_words())
+    }
+
+    fn finish_words(mut self) -> [u32; 8] {
+        // Close whichever framed sections are still open
+        if let Some(framing) = self.framing {
+            if !framing.in_data {
+                self.inner.update(&framing.aad_len.to_be_bytes());
+            }
+            self.inner.update(&framing.data_len.to_be_bytes());
+        }
+        // Finish both hashes in place so that Drop wipes everything they held
+        let inner_hash = Zeroizing::new(state_to_bytes(&self.inner.finish_in_place()));
+        self.outer.update(&*inner_hash);
+        self.outer.finish_in_place()
+    }
+}
+
+// The keyed states are as good as the key for forging tags, so wipe them, along
+// with any buffered data, when the context goes away
+impl Drop for Hmac {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+        self.outer.zeroize();
+        self.buffered.zeroize();
+    }
+}
+
+/// A key-bound HMAC-SHA256 signer for MACing many messages under one key.
+///
+/// The key is normalized and both pad blocks are compressed once in `new`, so
+/// each `sign` or `verify` only hashes the message and the final outer block.
+/// Tags equal `hmac(data, key)`. Dropping the signer wipes the precomputed state.
+#[derive(Clone)]
+pub struct HmacSha256 {
+    mac: Hmac,
+}
+
+impl HmacSha256 {
+    pub fn new(key: &[u8]) -> Self {
+        HmacSha256 { mac: Hmac::new(key) }
+    }
+
+    #[must_use]
+    pub fn sign(&self, data: &[u8]) -> Vec<u8> {
+        self.mac.sign(data)
+    }
+
+    // Checks the tag in constant time, without allocating
+    #[must_use]
+    pub fn verify(&self, data: &[u8], tag: &[u8]) -> bool {
+        let mut mac = self.mac.clone();
+        mac.update(data);
+        ct_equal(&mac.finish(), tag)
+    }
 }
 
-pub fn verify_hmac(data: &[u8], received_mac_tag: &[u8], key: &[u8]) -> bool {
-    let computed_mac_tag = hmac(data, key);
+/// Checks `received_mac_tag` against the HMAC of `data` under `key`.
+///
+/// The threat model is an attacker who controls the tag entirely, its bytes and
+/// its length, and can time many verifications. The HMAC is always computed in
+/// full, a tag of any length other than 32 bytes is rejected without reading
+/// its contents, and a 32-byte tag is compared in constant time against the
+/// fixed-size computed tag, so the timing depends only on the public lengths of
+/// the data and tag, never on how many tag bytes are right. The key and data are
+/// assumed to be the caller's, not the attacker's.
+#[must_use]
+pub fn verify_hmac(data: impl AsRef<[u8]>, received_mac_tag: &[u8], key: impl AsRef<[u8]>) -> bool {
+    let computed_mac_tag = hmac_fixed(data.as_ref(), key.as_ref());
+
+    // subtle's slice comparison returns early on a length mismatch. Only the
+    // length leaks that way, but pinning the tag to 32 bytes keeps every
+    // comparison the same fixed size
+    let Ok(received_mac_tag) = <&[u8; 32]>::try_from(received_mac_tag) else {
+        return false;
+    };
 
     // Perform a constant-time comparison to mitigate timing attacks
-    use subtle::ConstantTimeEq;
-    computed_mac_tag.ct_eq(received_mac_tag).unwrap_u8() == 1
+    ct_equal(&computed_mac_tag, received_mac_tag)
+}
+
+/// Verifies a tag like `verify_hmac`, but reports a tag that isn't 32 bytes as an error.
+///
+/// A wrong-length tag is almost always a caller bug, such as a tag still in hex
+/// or cut short in transit, so it gets `VerifyError::TagLength` instead of a
+/// bare `false`. Tags of the right length are compared in constant time, and
+/// `Ok(false)` means a well-formed tag that doesn't match.
+pub fn verify_hmac_checked(data: &[u8], received_mac_tag: &[u8], key: &[u8]) -> Result<bool, VerifyError> {
+    if received_mac_tag.len() != 32 {
+        return Err(VerifyError::TagLength { expected: 32, got: received_mac_tag.len() });
+    }
+    Ok(verify_hmac(data, received_mac_tag, key))
+}
+
+/// Verifies a hex-encoded tag, as received in an HTTP header, like `verify_hmac_checked`.
+///
+/// Malformed hex is `VerifyError::Encoding` and hex of anything but 32 bytes is
+/// `VerifyError::TagLength`, so a bad request can be told apart from a forged
+/// tag, which is `Ok(false)`. Upper and lower case digits are both accepted.
+/// The decoding isn't constant time, but it only reveals the tag the caller sent.
+pub fn verify_hmac_hex(data: &[u8], received_hex: &str, key: &[u8]) -> Result<bool, VerifyError> {
+    let received_mac_tag = hex::decode(received_hex)?;
+    verify_hmac_checked(data, &received_mac_tag, key)
+}
+
+/// Verifies a tag like `verify_hmac` without allocating.
+///
+/// The key is normalized and the tag is computed into a `[u8; 32]` on the stack,
+/// which is fully written before the constant-time comparison reads it.
+#[must_use]
+pub fn verify_hmac_stack(data: &[u8], received_mac_tag: &[u8], key: &[u8]) -> bool {
+    let mut mac = Hmac::new(key);
+    mac.update(data);
+    let computed_mac_tag: [u8; 32] = mac.finish();
+
+    ct_equal(&computed_mac_tag, received_mac_tag)
+}
+
+/// Verifies a 32-byte tag under a 32-byte key with no heap use at all.
+///
+/// The fixed lengths mean no allocator and no length branches, so this can run
+/// in interrupt handlers. The comparison is the same constant-time `ct_equal`.
+#[must_use]
+pub fn verify_hmac_no_alloc(data: &[u8], received: &[u8; 32], key: &[u8; 32]) -> bool {
+    let mut mac = Hmac::new(key);
+    mac.update(data);
+    let computed: [u8; 32] = mac.finish();
+
+    ct_equal(&computed, received)
+}
+
+/// Verifies a tag under the key registered for `keyid`, returning `false` for an unknown id.
+///
+/// An unknown key id still computes an HMAC under a dummy key and runs the
+/// constant-time comparison, so the timing does not reveal whether the id
+/// exists. The map lookup itself is not constant time.
+#[cfg(feature = "std")]
+#[must_use]
+pub fn verify_hmac_by_keyid(
+    data: &[u8],
+    received_mac_tag: &[u8],
+    keys: &std::collections::HashMap<String, Vec<u8>>,
+    keyid: &str,
+) -> bool {
+    match keys.get(keyid) {
+        Some(key) => verify_hmac(data, received_mac_tag, key),
+        None => {
+            // Do the same work as a real verification, then discard the result
+            let dummy_key = [0_u8; BLOCKSIZE];
+            core::hint::black_box(verify_hmac(data, received_mac_tag, dummy_key));
+            false
+        }
+    }
+}
+
+/// Computes the HMAC and keeps its leftmost `tag_len` bytes, as in HMAC-SHA256-128.
+///
+/// Fails with `Error::TagLength` unless `tag_len` is between 1 and 32. Check
+/// these tags with `verify_hmac_truncated`, not `verify_hmac`.
+pub fn hmac_truncated(data: &[u8], key: &[u8], tag_len: usize) -> Result<Vec<u8>, Error> {
+    if !(1..=32).contains(&tag_len) {
+        return Err(Error::TagLength { len: tag_len });
+    }
+    Ok(hmac_fixed(data, key)[..tag_len].to_vec())
+}
+
+/// Verifies a tag from `hmac_truncated` with the same `tag_len`.
+///
+/// Only the first `tag_len` bytes of the computed HMAC are compared, in
+/// constant time, so the comparison length is the expected tag length. A tag
+/// of any other length, or an invalid `tag_len`, verifies as `false`.
+#[must_use]
+pub fn verify_hmac_truncated(data: &[u8], received_mac_tag: &[u8], key: &[u8], tag_len: usize) -> bool {
+    if !(1..=32).contains(&tag_len) || received_mac_tag.len() != tag_len {
+        return false;
+    }
+    let computed = hmac_fixed(data, key);
+    ct_equal(&computed[..tag_len], received_mac_tag)
+}
+
+/// Computes the HMAC truncated to its leftmost `bits` bits.
+///
+/// Returns `ceil(bits / 8)` bytes. When `bits` is not a multiple of 8, the low
+/// `8 - bits % 8` bits of the final byte are cleared, so only the kept bits are
+/// ever nonzero. Panics unless `bits` is in `1..=256`.
+#[must_use]
+pub fn hmac_truncated_bits(data: &[u8], key: &[u8], bits: usize) -> Vec<u8> {
+    assert!((1..=256).contains(&bits), "truncation must keep between 1 and 256 bits, got {}", bits);
+    let mut mac = Hmac::new(key);
+    mac.update(data);
+    let mut tag = mac.finish()[..bits.div_ceil(8)].to_vec();
+    mask_trailing_bits(&mut tag, bits);
+    tag
+}
+
+// Clears the bits of the last byte past `bits`, keeping the high-order ones
+fn mask_trailing_bits(tag: &mut [u8], bits: usize) {
+    let kept = bits % 8;
+    if kept != 0 {
+        if let Some(last) = tag.last_mut() {
+            *last &= 0xff << (8 - kept);
+        }
+    }
+}
+
+/// Verifies a tag like `verify_hmac`, calling `on_fail` if it does not match.
+///
+/// The callback only runs after the full constant-time comparison has finished,
+/// so its timing says nothing about where the tags differed.
+#[must_use]
+pub fn verify_hmac_audited(data: &[u8], received_mac_tag: &[u8], key: &[u8], on_fail: impl FnOnce()) -> bool {
+    let valid = verify_hmac(data, received_mac_tag, key);
+    if !valid {
+        on_fail();
+    }
+    valid
+}
+
+/// Verifies a tag produced by `hmac_truncated_bits` with the same `bits`.
+///
+/// Only the leftmost `bits` bits take part: the bits the truncation masked off
+/// are masked off `received_mac_tag` as well before the constant-time
+/// comparison. A tag of the wrong byte length, or a `bits` outside `1..=256`,
+/// verifies as `false` rather than panicking.
+#[must_use]
+pub fn verify_hmac_truncated_bits(data: &[u8], received_mac_tag: &[u8], key: &[u8], bits: usize) -> bool {
+    if !(1..=256).contains(&bits) {
+        return false;
+    }
+    let expected = hmac_truncated_bits(data, key, bits);
+    if received_mac_tag.len() != expected.len() {
+        return false;
+    }
+    let mut received = [0_u8; 32];
+    let received = &mut received[..expected.len()];
+    received.copy_from_slice(received_mac_tag);
+    mask_trailing_bits(received, bits);
+
+    ct_equal(&expected, received)
+}
+
+/// Returns a value that commits to `key`, computed as `hmac(b"commitment", key)`.
+///
+/// Sending this alongside a tag binds the tag to one specific key, which stops
+/// key-substitution attacks where a second key is found that also verifies.
+/// Commitments are secret-derived and must be compared in constant time.
+pub fn key_commitment(key: &[u8]) -> [u8; 32] {
+    hmac_fixed(b"commitment", key)
+}
+
+/// Commits to `value` as `hash(nonce || value)`, to be opened later by revealing both.
+///
+/// The nonce must be high-entropy and kept secret until the reveal, otherwise a
+/// low-entropy value can be guessed from the commitment. Use a fixed nonce length
+/// so the boundary between nonce and value is unambiguous.
+pub fn commit(value: &[u8], nonce: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(nonce);
+    hasher.update(value);
+    hasher.finish()
+}
+
+// Checks a revealed value and nonce against a commitment in constant time
+#[must_use]
+pub fn verify_commit(commitment: &[u8; 32], value: &[u8], nonce: &[u8]) -> bool {
+    ct_equal(&commit(value, nonce), commitment)
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hex::encode;
+    use crate::utils::pad;
 
     #[test]
     fn test_hash() {
@@ -140,7 +1114,7 @@ mod tests {
         let message_bytes: Vec<u8> = message.as_bytes().to_vec();
         let hash_value = hash(&message_bytes);
 
-        let hex_string = to_hex_string(&hash_value);
+        let hex_string = encode(&hash_value);
         let target_hex_string: String= "0668b515bfc41b90b6a90a6ae8600256e1c76a67d17c78a26127ddeb9b324435".to_string();
 
         assert_eq!(hex_string, target_hex_string);
@@ -153,26 +1127,853 @@ mod tests {
         let message_bytes = message.as_bytes().to_vec();
         let hmac_value = hmac(&message_bytes, &key);
 
-        let hex_string = to_hex_string(&hmac_value);
+        let hex_string = encode(&hmac_value);
         let target_hex_string: String = "198a607eb44bfbc69903a0f1cf2bbdc5ba0aa3f3d9ae3c1c7a3b1696a0b68cf7".to_string();
 
         assert_eq!(hex_string, target_hex_string);
     }
 
+    // All seven HMAC-SHA-256 cases from RFC 4231 section 4
     #[test]
-    fn test_verify_hmac() {
-        let key = vec![0xa; 32];
-        let message: &str = "dddddddddddddddddddddddddddddddddddddddddddddddddd";
-        let message_bytes = message.as_bytes().to_vec();
-        let hmac_value = hmac(&message_bytes, &key);
-        let mut modified_hmac_value = hmac_value.clone();
-        modified_hmac_value[0] = 0xff;
+    fn test_hmac_rfc4231_vectors() {
+        let key_4: Vec<u8> = (0x01..=0x19).collect();
+        let cases: [(&[u8], &[u8], &str); 6] = [
+            (&[0x0b; 20], b"Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+            (b"Jefe", b"what do ya want for nothing?", "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+            (&[0xaa; 20], &[0xdd; 50], "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"),
+            (&key_4, &[0xcd; 50], "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b"),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. \
+                  The key needs to be hashed before being used by the HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+        for (key, data, expected) in cases {
+            assert_eq!(encode(&hmac(data, key)), expected, "key of {} bytes", key.len());
+            assert!(verify_hmac(data, &hmac(data, key), key));
 
-        assert_eq!(verify_hmac(&message_bytes, &hmac_value, &key), true);
-        assert_eq!(verify_hmac(&message_bytes, &modified_hmac_value, &key), false);
+            let mut mac = Hmac::new(key);
+            mac.update(data);
+            assert_eq!(encode(&mac.finalize()), expected);
+        }
+
+        // Case 5 keeps only the leftmost 128 bits
+        let truncated = hmac_truncated(b"Test With Truncation", &[0x0c; 20], 16).unwrap();
+        assert_eq!(encode(&truncated), "a3b6167473100ee06e0c796c2955552b");
+        assert!(verify_hmac_truncated(b"Test With Truncation", &truncated, &[0x0c; 20], 16));
+    }
+
+    // Keys past the block size are replaced by their digest, zero padded
+    #[test]
+    fn test_normalize_long_key() {
+        let key = [0xaa_u8; 131];
+        let normalized = normalize(&key);
+
+        assert_eq!(normalized[..32], hash(key)[..]);
+        assert_eq!(normalized[32..], [0_u8; 32]);
+        assert_eq!(normalize(&[0xaa; BLOCKSIZE]), [0xaa; BLOCKSIZE]);
     }
 
-    fn to_hex_string(bytes: &[u8]) -> String {
-        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    #[test]
+    fn test_hash_empty_input() {
+        let expected = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        assert_eq!(encode(&hash(b"")), expected);
+        assert_eq!(encode(&Sha256::new().finalize()), expected);
+        assert_eq!(hash_many(&[b"", b""]), hash(b""));
+    }
+
+    // Reference tags from Python's hmac module
+    #[test]
+    fn test_hmac_empty_key() {
+        let fox = b"The quick brown fox jumps over the lazy dog";
+
+        assert_eq!(encode(&hmac(b"", b"")), "b613679a0814d9ec772f95d778c35fc5ff1697c493715653c6c712144292c5ad");
+        assert_eq!(encode(&hmac(fox, b"")), "fb011e6154a19b9a4c767373c305275a5a69e8b68b0b4c9200c383dced19a416");
+
+        let mut mac = Hmac::new(b"");
+        mac.update(fox);
+        assert_eq!(mac.finalize(), hmac(fox, b""));
+    }
+
+    // RFC 2104 pads a short key with zeros, so the empty key is the all-zero block
+    #[test]
+    fn test_normalize_empty_key() {
+        assert_eq!(normalize(b""), [0_u8; BLOCKSIZE]);
+        assert_eq!(hmac(b"message", b""), hmac(b"message", [0_u8; BLOCKSIZE]));
+        assert_eq!(hmac(b"message", b""), hmac(b"message", [0_u8; 20]));
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn test_verify_hmac() {
+        let key = vec![0xa; 32];
+        let message: &str = "dddddddddddddddddddddddddddddddddddddddddddddddddd";
+        let message_bytes = message.as_bytes().to_vec();
+        let hmac_value = hmac(&message_bytes, &key);
+        let mut modified_hmac_value = hmac_value.clone();
+        modified_hmac_value[0] = 0xff;
+
+        assert_eq!(verify_hmac(&message_bytes, &hmac_value, &key), true);
+        assert_eq!(verify_hmac(&message_bytes, &modified_hmac_value, &key), false);
+    }
+
+    #[test]
+    fn test_sha256_streaming_matches_hash() {
+        let data: Vec<u8> = (0..300_u32).map(|i| (i * 7) as u8).collect();
+        for chunk_size in [1, 7, 63, 64, 65, 300] {
+            let mut hasher = Sha256::new();
+            for chunk in data.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), hash(&data));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_digest() {
+        let prefix = b"The quick brown fox jumps over the lazy dog, ".repeat(3);
+        let suffix = b"and keeps running.";
+
+        let mut hasher = Sha256::new();
+        hasher.update(&prefix);
+        let snapshot = hasher.snapshot_digest();
+        hasher.update(suffix);
+
+        assert_eq!(snapshot.to_vec(), hash(&prefix));
+        assert_eq!(hasher.finalize(), hash([prefix.as_slice(), suffix].concat()));
+    }
+
+    #[test]
+    fn test_key_commitment() {
+        let commitment = key_commitment(b"first key");
+
+        assert_eq!(commitment, key_commitment(b"first key"));
+        assert_ne!(commitment, key_commitment(b"second key"));
+        assert_eq!(commitment.to_vec(), hmac(b"commitment", b"first key"));
+    }
+
+    #[test]
+    fn test_verify_hmac_audited() {
+        let key = b"audit key";
+        let message = b"audited message";
+        let tag = hmac(message, key);
+        let mut forged = tag.clone();
+        forged[31] ^= 0x01;
+
+        let mut failures = 0;
+        assert!(verify_hmac_audited(message, &tag, key, || failures += 1));
+        assert_eq!(failures, 0);
+
+        assert!(!verify_hmac_audited(message, &forged, key, || failures += 1));
+        assert_eq!(failures, 1);
+    }
+
+    #[test]
+    fn test_hmac_vectored() {
+        let key = b"vectored key";
+        let header = b"POST /api/v1/orders HTTP/1.1\r\n";
+        let body = vec![0x42_u8; 150];
+        let trailer = b"checksum";
+        let slices: [&[u8]; 3] = [header, &body, trailer];
+
+        assert_eq!(hmac_vectored(&slices, key), hmac(slices.concat(), key));
+        assert_eq!(hmac_vectored(&[], key), hmac(b"", key));
+    }
+
+    #[test]
+    fn test_hmac_streaming_matches_hmac() {
+        let key = b"streaming key";
+        let data = vec![0x61_u8; 200];
+
+        let mut mac = Hmac::new(key);
+        for chunk in data.chunks(33) {
+            mac.update(chunk);
+        }
+        assert_eq!(mac.finalize(), hmac(&data, key));
+    }
+
+    #[test]
+    fn test_hmac_from_prehashed_key() {
+        let long_key = vec![0xaa_u8; 131];
+        let digest: [u8; 32] = hash(&long_key).try_into().unwrap();
+        let message = b"Test Using Larger Than Block-Size Key - Hash Key First";
+
+        let mut prehashed = Hmac::from_prehashed_key(&digest);
+        prehashed.update(message);
+        let mut direct = Hmac::new(&long_key);
+        direct.update(message);
+
+        let tag = prehashed.finalize();
+        assert_eq!(tag, direct.finalize());
+        assert_eq!(tag, hmac(message, &long_key));
+    }
+
+    #[test]
+    fn test_hmac_short_path_boundaries() {
+        let key = b"short path key";
+        for len in [0, 1, 32, 54, 55, 56, 57, 63, 64, 65] {
+            let data = vec![0x33_u8; len];
+            let mut mac = Hmac::new(key);
+            mac.update(&data);
+
+            assert_eq!(hmac(&data, key), mac.finalize(), "length {}", len);
+            if len <= SHORT_MESSAGE_LEN {
+                assert_eq!(hmac_short(&data, key).to_vec(), hmac_vectored(&[&data], key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_hmac_stack() {
+        for key in [b"short key".to_vec(), vec![0x0c_u8; 100]] {
+            let message = b"stack verified message";
+            let tag = hmac(message, &key);
+            let mut forged = tag.clone();
+            forged[5] ^= 0x80;
+
+            for candidate in [tag.clone(), forged, tag[..31].to_vec(), Vec::new()] {
+                assert_eq!(verify_hmac_stack(message, &candidate, &key), verify_hmac(message, &candidate, &key));
+            }
+            assert!(verify_hmac_stack(message, &tag, &key));
+        }
+    }
+
+    #[test]
+    fn test_hash_pair_framing() {
+        let framed = [&2_u64.to_be_bytes()[..], b"ab", &3_u64.to_be_bytes()[..], b"cde"].concat();
+        assert_eq!(hash_pair(b"ab", b"cde").to_vec(), hash(&framed));
+    }
+
+    #[test]
+    fn test_hash_pair_distinguishes_splits() {
+        assert_ne!(hash_pair(b"ab", b""), hash_pair(b"", b"ab"));
+        assert_ne!(hash_pair(b"a", b"b"), hash_pair(b"ab", b""));
+        assert_ne!(hash_pair(b"a", b"bc"), hash_pair(b"ab", b"c"));
+        assert_eq!(hash_pair(b"a", b"bc"), hash_pair(b"a", b"bc"));
+    }
+
+    #[test]
+    fn test_size_constants() {
+        let digest: [u8; OUTPUT_SIZE] = hash_fixed(b"sized");
+        assert_eq!(digest.len(), hash(b"sized").len());
+        assert_eq!(Sha256::OUTPUT_SIZE, 32);
+        assert_eq!(Sha256::BLOCK_SIZE, 64);
+        assert_eq!(BLOCK_SIZE, BLOCKSIZE);
+    }
+
+    #[test]
+    fn test_sha256_clone_and_reset() {
+        let prefix = vec![0x70_u8; 90];
+        let mut hasher = Sha256::new();
+        hasher.update(&prefix);
+
+        let mut fork = hasher.clone();
+        hasher.update(b"first suffix");
+        fork.update(b"a different, longer second suffix");
+        assert_eq!(hasher.clone().finalize(), hash([&prefix[..], b"first suffix"].concat()));
+        assert_eq!(fork.finalize(), hash([&prefix[..], b"a different, longer second suffix"].concat()));
+
+        hasher.reset();
+        hasher.update(b"abc");
+        assert_eq!(hasher.finalize(), hash(b"abc"));
+    }
+
+    #[test]
+    fn test_as_ref_inputs() {
+        let message = String::from("hello");
+        let key = vec![0x0b_u8; 20];
+        let expected = hmac(message.as_bytes(), key.as_slice());
+
+        assert_eq!(hash("hello"), hash(message.as_bytes()));
+        assert_eq!(hash(vec![1_u8, 2, 3]), hash([1_u8, 2, 3]));
+        assert_eq!(hmac(&message, &key), expected);
+        assert_eq!(hmac("hello", [0x0b_u8; 20]), expected);
+        assert!(verify_hmac(message, &expected, key));
+    }
+
+    // 55 bytes leave just room for 0x80 and the length; 56 and 63 spill into a second block
+    #[test]
+    fn test_finalize_padding_spill() {
+        for prefix_len in [0, 64, 192] {
+            for leftover in [55, 56, 63] {
+                let data = vec![0x9c_u8; prefix_len + leftover];
+                let mut hasher = Sha256::new();
+                hasher.update(&data[..prefix_len]);
+                hasher.update(&data[prefix_len..]);
+                assert_eq!(hasher.buffer_len, leftover);
+
+                let expected = hash_prepadded(&pad(&data).unwrap()).unwrap();
+                assert_eq!(hasher.finalize(), expected, "{} + {} bytes", prefix_len, leftover);
+            }
+        }
+    }
+
+    // The previous compress, which expanded the full 64-word schedule up front
+    fn compress_with_full_schedule(state: &mut [u32; 8], block: &[u32; 16]) {
+        let mut message_schedule: [u32; 64] = [0_u32; 64];
+        for t in 0..64 {
+            if t < 16 {
+                message_schedule[t] = block[t];
+            } else {
+                message_schedule[t] = sigma_256_1(message_schedule[t - 2])
+                                                .wrapping_add(message_schedule[t - 7])
+                                                .wrapping_add(sigma_256_0(message_schedule[t - 15]))
+                                                .wrapping_add(message_schedule[t - 16]);
+            }
+        }
+        compress_scheduled(state, &message_schedule);
+    }
+
+    #[test]
+    fn test_rolling_compress_matches_full_schedule() {
+        for len in [0, 3, 55, 56, 64, 119, 1000] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 1) as u8).collect();
+            let mut rolling = INITIAL_HASH;
+            let mut full = INITIAL_HASH;
+            for block in &parse(&pad(&data).unwrap()).unwrap() {
+                compress(&mut rolling, block);
+                compress_with_full_schedule(&mut full, block);
+                assert_eq!(rolling, full, "length {}", len);
+            }
+        }
+    }
+
+    // HMAC spelled out with concatenated buffers, as hmac_fixed used to compute it
+    #[test]
+    fn test_hmac_streamed_inner_matches_concatenation() {
+        for key in [&b"key"[..], &[0x0b; 32], &[0xaa; 131]] {
+            for len in [56, 64, 200, 1000] {
+                let data = vec![0x42_u8; len];
+                let (inner_key, outer_key) = key_pads(key);
+                let inner_hash = hash([&inner_key[..], &data].concat());
+                let expected = hash([&outer_key[..], &inner_hash].concat());
+
+                assert_eq!(hmac(&data, key), expected, "key of {} bytes, length {}", key.len(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_many() {
+        let header = b"header straddling a block";
+        let payload = vec![0x5a_u8; 100];
+        let empty: &[u8] = b"";
+        let chunks: [&[u8]; 4] = [header, empty, &payload, &payload[..39]];
+
+        assert_eq!(hash_many(&chunks), hash(chunks.concat()));
+        assert_eq!(hash_many(&[]), hash(b""));
+    }
+
+    #[test]
+    fn test_hmac_finalize_into() {
+        let key = b"finalize into key";
+        let message = b"message for a caller buffer";
+        let mut mac = Hmac::new(key);
+        mac.update(message);
+        let expected = hmac(message, key);
+
+        let mut exact = [0_u8; 32];
+        mac.clone().finalize_into(&mut exact).unwrap();
+        assert_eq!(exact.to_vec(), expected);
+
+        let mut oversized = [0xee_u8; 40];
+        mac.clone().finalize_into(&mut oversized).unwrap();
+        assert_eq!(oversized[..32].to_vec(), expected);
+        assert_eq!(oversized[32..], [0xee_u8; 8]);
+
+        let mut small = [0_u8; 31];
+        assert_eq!(mac.finalize_into(&mut small), Err(Error::BufferTooSmall { needed: 32, got: 31 }));
+    }
+
+    #[test]
+    fn test_sha256_finalize_into() {
+        let mut hasher = Sha256::new();
+        hasher.update(b"abc");
+        let mut out = [0_u8; 32];
+        hasher.clone().finalize_into(&mut out).unwrap();
+
+        assert_eq!(out.to_vec(), hash(b"abc"));
+        assert_eq!(hasher.finalize_into(&mut [0_u8; 16]), Err(Error::BufferTooSmall { needed: 32, got: 16 }));
+    }
+
+    #[test]
+    fn test_hash_verify() {
+        let data = b"integrity checked payload";
+        let digest = hash(data);
+        let mut corrupted = digest.clone();
+        corrupted[10] ^= 0x04;
+
+        assert!(hash_verify(data, &digest));
+        assert!(!hash_verify(data, &corrupted));
+        assert!(!hash_verify(data, &digest[..31]));
+        assert!(!hash_verify(data, &[digest.as_slice(), &[0]].concat()));
+        assert!(!hash_verify(data, &[]));
+    }
+
+    #[test]
+    fn test_hmac_with_key_and_prefix() {
+        let key = b"protocol key";
+        let prefix = b"proto/v2:";
+        let mac = Hmac::with_key_and_prefix(key, prefix);
+
+        for message in [&b""[..], b"ping", &[0x7f; 120]] {
+            assert_eq!(mac.sign(message), hmac([prefix.as_slice(), message].concat(), key));
+        }
+    }
+
+    #[test]
+    fn test_hash_assume_init() {
+        use std::mem::MaybeUninit;
+
+        let expected: Vec<u8> = (0..100_u8).collect();
+        let mut buffer = [MaybeUninit::<u8>::uninit(); 100];
+        for (slot, byte) in buffer.iter_mut().zip(expected.iter()) {
+            slot.write(*byte);
+        }
+
+        // SAFETY: every byte of buffer was written above
+        let digest = unsafe { hash_assume_init(&buffer) };
+        assert_eq!(digest, hash(&expected));
+    }
+
+    #[test]
+    fn test_hash_prepadded() {
+        for len in [0, 3, 55, 56, 64, 200] {
+            let data = vec![0x99_u8; len];
+            assert_eq!(hash_prepadded(&pad(&data).unwrap()).unwrap(), hash(&data));
+        }
+
+        assert_eq!(hash_prepadded(&[0_u8; 65]), Err(Error::Hash(HashError::UnalignedInput { len: 65 })));
+    }
+
+    #[test]
+    fn test_commit_reveal() {
+        let nonce = [0x3c_u8; 32];
+        let commitment = commit(b"heads", &nonce);
+
+        assert_eq!(commitment.to_vec(), hash([nonce.as_slice(), b"heads"].concat()));
+        assert!(verify_commit(&commitment, b"heads", &nonce));
+        assert!(!verify_commit(&commitment, b"tails", &nonce));
+        assert!(!verify_commit(&commitment, b"heads", &[0x3d_u8; 32]));
+    }
+
+    #[test]
+    fn test_hash_counted() {
+        let data = vec![0x10_u8; 777];
+        let (digest, count) = hash_counted(&data);
+        assert_eq!(digest.to_vec(), hash(&data));
+        assert_eq!(count, 777);
+
+        let mut hasher = Sha256::new();
+        for chunk in data.chunks(100) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize_counted(), (hash(&data), 777));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_verify_hmac_by_keyid() {
+        let mut keys = std::collections::HashMap::new();
+        keys.insert("tenant-a".to_string(), b"key for tenant a".to_vec());
+        keys.insert("tenant-b".to_string(), b"key for tenant b".to_vec());
+
+        let message = b"multi-tenant request";
+        let tag = hmac(message, b"key for tenant a");
+
+        assert!(verify_hmac_by_keyid(message, &tag, &keys, "tenant-a"));
+        assert!(!verify_hmac_by_keyid(message, &tag, &keys, "tenant-b"));
+        assert!(!verify_hmac_by_keyid(message, &tag, &keys, "tenant-c"));
+
+        // Even a tag made with the dummy key must not verify for an unknown id
+        let dummy_tag = hmac(message, [0_u8; BLOCKSIZE]);
+        assert!(!verify_hmac_by_keyid(message, &dummy_tag, &keys, "tenant-c"));
+    }
+
+    #[test]
+    fn test_hash_bits_nist_vectors() {
+        // Bit-oriented vectors from the NIST SHAVS: one zero bit, and the five bits 01101
+        assert_eq!(encode(&hash_bits(&[0x00], 1)),
+                   "bd4f9e98beb68c6ead3243b1b4c7fed75fa4feaab1f84795cbd8a98676a2a375");
+        assert_eq!(encode(&hash_bits(&[0x68], 5)),
+                   "d6d3e02a31a84a8caa9718ed6c2057be09db45e7823eb5079ce7a573a3760f95");
+
+        // Trailing bits beyond bit_len do not matter
+        assert_eq!(hash_bits(&[0x6f], 5), hash_bits(&[0x68], 5));
+    }
+
+    #[test]
+    fn test_hash_bits_whole_bytes() {
+        let data = b"This is a test message.";
+        assert_eq!(hash_bits(data, data.len() * 8), hash(data));
+        assert_eq!(hash_bits(b"", 0), hash(b""));
+    }
+
+    #[test]
+    fn test_hash_and_hmac_words() {
+        let data = b"words rather than bytes";
+        let key = b"word key";
+
+        let to_bytes = |words: [u32; 8]| words.iter().flat_map(|word| word.to_be_bytes()).collect::<Vec<u8>>();
+        assert_eq!(to_bytes(hash_words(data)), hash(data));
+        assert_eq!(to_bytes(hmac_words(data, key)), hmac(data, key));
+        assert_eq!(hash_words(b"abc")[0], 0xba7816bf);
+    }
+
+    #[test]
+    fn test_checkpoint_restore() {
+        let accepted = b"header:ok;".repeat(10);
+        let rejected = b"segment that fails validation";
+
+        let mut hasher = Sha256::new();
+        hasher.update(&accepted);
+        let checkpoint = hasher.checkpoint();
+        hasher.update(rejected);
+        hasher.restore(checkpoint.clone());
+        assert_eq!(hasher.snapshot_digest().to_vec(), hash(&accepted));
+
+        hasher.update(b"trailer");
+        assert_eq!(hasher.finalize(), hash([accepted.as_slice(), b"trailer"].concat()));
+
+        // A checkpoint can be restored into a different hasher, more than once
+        let mut other = Sha256::new();
+        other.update(b"unrelated");
+        other.restore(checkpoint);
+        assert_eq!(other.finalize(), hash(&accepted));
+    }
+
+    #[test]
+    fn test_hash_fixed_arrays() {
+        let empty: [u8; 0] = [];
+        let thirty_two = [0x20_u8; 32];
+        let hundred = [0x64_u8; 100];
+
+        assert_eq!(encode(&hash(empty)), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hash(thirty_two), hash(&thirty_two[..]));
+        assert_eq!(hash(hundred), hash(&hundred[..]));
+    }
+
+    #[test]
+    fn test_verify_hmac_no_alloc() {
+        let key = [0x4b_u8; 32];
+        let data = b"interrupt payload";
+        let tag: [u8; 32] = hmac(data, key).try_into().unwrap();
+        let mut wrong = tag;
+        wrong[31] ^= 1;
+
+        assert!(verify_hmac_no_alloc(data, &tag, &key));
+        assert!(!verify_hmac_no_alloc(data, &wrong, &key));
+        assert_eq!(verify_hmac_no_alloc(data, &tag, &key), verify_hmac(data, &tag, key));
+        assert_eq!(verify_hmac_no_alloc(data, &wrong, &key), verify_hmac(data, &wrong, key));
+    }
+
+    #[test]
+    fn test_hmac_truncated_80_bits() {
+        let full = hmac(b"truncate me", b"key");
+        let tag = hmac_truncated_bits(b"truncate me", b"key", 80);
+
+        assert_eq!(tag, full[..10]);
+        assert!(verify_hmac_truncated_bits(b"truncate me", &tag, b"key", 80));
+        assert!(!verify_hmac_truncated_bits(b"truncate me", &full[..11], b"key", 80));
+    }
+
+    #[test]
+    fn test_hmac_truncated_100_bits() {
+        let full = hmac(b"truncate me", b"key");
+        let tag = hmac_truncated_bits(b"truncate me", b"key", 100);
+
+        assert_eq!(tag.len(), 13);
+        assert_eq!(tag[..12], full[..12]);
+        assert_eq!(tag[12], full[12] & 0xf0);
+        assert!(verify_hmac_truncated_bits(b"truncate me", &tag, b"key", 100));
+
+        // The masked-off low nibble is ignored, a kept bit is not
+        let mut unmasked = tag.clone();
+        unmasked[12] |= 0x0f;
+        assert!(verify_hmac_truncated_bits(b"truncate me", &unmasked, b"key", 100));
+        let mut flipped = tag.clone();
+        flipped[12] ^= 0x10;
+        assert!(!verify_hmac_truncated_bits(b"truncate me", &flipped, b"key", 100));
+    }
+
+    #[test]
+    fn test_verify_hmac_truncated_bits_rejects_bad_bits() {
+        let full = hmac(b"truncate me", b"key");
+        assert!(!verify_hmac_truncated_bits(b"truncate me", &[], b"key", 0));
+        assert!(!verify_hmac_truncated_bits(b"truncate me", &full, b"key", 257));
+        assert!(!verify_hmac_truncated_bits(b"truncate me", &full, b"key", usize::MAX));
+        assert!(verify_hmac_truncated_bits(b"truncate me", &full, b"key", 256));
+    }
+
+    #[test]
+    fn test_hash_u32_words() {
+        let words: Vec<u32> = (0..40).map(|i| 0x0102_0304_u32.wrapping_mul(i)).collect();
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+
+        assert_eq!(hash_u32_words(&words).to_vec(), hash(&bytes));
+        assert_eq!(hash_u32_words(&[]).to_vec(), hash(b""));
+    }
+
+    #[test]
+    fn test_length_field_counts_all_updates() {
+        let message = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        let expected = "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1";
+
+        // Uneven chunks that leave partial blocks behind between updates
+        let mut hasher = Sha256::new();
+        for chunk in [&message[..3], &message[3..60], &message[60..61], &message[61..]] {
+            hasher.update(chunk);
+        }
+        assert_eq!(encode(&hasher.finalize()), expected);
+        assert_eq!(encode(&hash(message)), expected);
+
+        // A final chunk shorter than a block must not be mistaken for the whole message
+        let mut hasher = Sha256::new();
+        hasher.update(&message[..100]);
+        hasher.update(&message[100..]);
+        assert_ne!(hasher.finalize(), hash(&message[100..]));
+    }
+
+    #[test]
+    fn test_fixed_outputs_match_vec_outputs() {
+        for len in [0, SHORT_MESSAGE_LEN, SHORT_MESSAGE_LEN + 1, 64, 300] {
+            let data = vec![0xa5_u8; len];
+
+            assert_eq!(hash_fixed(&data).to_vec(), hash(&data));
+            assert_eq!(hmac_fixed(&data, b"key").to_vec(), hmac(&data, b"key"));
+        }
+        assert_eq!(
+            encode(&hmac_fixed(b"The quick brown fox jumps over the lazy dog", b"key")),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_content_address() {
+        let path = content_address(b"abc");
+        let digest = encode(&hash(b"abc"));
+        let parts: Vec<&str> = path.split('/').collect();
+
+        assert_eq!(parts, [&digest[..2], &digest[2..4], digest.as_str()]);
+        assert_eq!(path, "ba/78/ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_finalize_verify() {
+        let key = b"pipeline key";
+        let tag = hmac(b"header:body", key);
+
+        let mut mac = Hmac::new(key);
+        mac.update_and_buffer(b"header:");
+        mac.update_and_buffer(b"body");
+        assert_eq!(mac.finalize_verify(&tag), Some(b"header:body".to_vec()));
+
+        let mut corrupted = tag.clone();
+        corrupted[0] ^= 1;
+        let mut mac = Hmac::new(key);
+        mac.update_and_buffer(b"header:body");
+        assert_eq!(mac.finalize_verify(&corrupted), None);
+    }
+
+    #[test]
+    fn test_finalize_checked() {
+        let expected = hash(b"download");
+        let mut wrong = expected.clone();
+        wrong[5] ^= 0x40;
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"download");
+        assert_eq!(hasher.clone().finalize_checked(Some(&expected)), (expected.clone(), true));
+        assert_eq!(hasher.clone().finalize_checked(Some(&wrong)), (expected.clone(), false));
+        assert_eq!(hasher.finalize_checked(None), (expected, true));
+    }
+
+    #[test]
+    fn test_hmac_sha256_context() {
+        let signer = HmacSha256::new(b"session key");
+
+        for packet in [b"".as_slice(), b"packet one", &[0x42; 200]] {
+            let tag = signer.sign(packet);
+            assert_eq!(tag, hmac(packet, b"session key"));
+            assert!(signer.verify(packet, &tag));
+            assert!(!signer.verify(packet, &tag[..31]));
+        }
+        assert!(!signer.verify(b"packet one", &hmac(b"packet two", b"session key")));
+    }
+
+    #[test]
+    fn test_hmac_aad_framing() {
+        let key = b"aead key";
+        let mut mac = Hmac::new(key);
+        mac.update_aad(b"header");
+        mac.update_data(b"payload");
+        let tag = mac.finalize();
+
+        let framed = [b"header".as_slice(), &6_u64.to_be_bytes(), b"payload", &7_u64.to_be_bytes()].concat();
+        assert_eq!(tag, hmac(&framed, key));
+
+        // Chunking does not matter
+        let mut mac = Hmac::new(key);
+        mac.update_aad(b"hea");
+        mac.update_aad(b"der");
+        mac.update_data(b"pay");
+        mac.update_data(b"");
+        mac.update_data(b"load");
+        assert_eq!(mac.finalize(), tag);
+
+        // Moving a byte across the boundary does
+        let mut mac = Hmac::new(key);
+        mac.update_aad(b"headerp");
+        mac.update_data(b"ayload");
+        assert_ne!(mac.finalize(), tag);
+
+        // AAD alone still gets both frames
+        let mut mac = Hmac::new(key);
+        mac.update_aad(b"header");
+        let aad_only = [b"header".as_slice(), &6_u64.to_be_bytes(), &0_u64.to_be_bytes()].concat();
+        assert_eq!(mac.finalize(), hmac(&aad_only, key));
+    }
+
+    #[test]
+    #[should_panic(expected = "update_aad called after update_data")]
+    fn test_hmac_aad_after_data_panics() {
+        let mut mac = Hmac::new(b"aead key");
+        mac.update_data(b"payload");
+        mac.update_aad(b"header");
+    }
+
+    // Best effort: the words live inline in the struct, so they can still be read
+    // through a raw pointer after dropping in place, before the slot is reused
+    #[test]
+    fn test_hmac_sha256_wiped_on_drop() {
+        let mut signer = core::mem::ManuallyDrop::new(HmacSha256::new(b"secret key"));
+        let inner_state: *const [u32; 8] = &signer.mac.inner.state;
+        let outer_state: *const [u32; 8] = &signer.mac.outer.state;
+        assert_ne!(unsafe { core::ptr::read_volatile(inner_state) }, [0; 8]);
+
+        unsafe { core::mem::ManuallyDrop::drop(&mut signer) };
+        assert_eq!(unsafe { core::ptr::read_volatile(inner_state) }, [0; 8]);
+        assert_eq!(unsafe { core::ptr::read_volatile(outer_state) }, [0; 8]);
+    }
+
+    #[test]
+    fn test_compress_block() {
+        // "abc" padded to a single block
+        let mut block = [0_u32; 16];
+        block[0] = 0x6162_6380;
+        block[15] = 24;
+
+        assert_eq!(compress_block(INITIAL_HASH, block), hash_words(b"abc"));
+        assert_ne!(compress_block([0; 8], block), hash_words(b"abc"));
+    }
+
+    #[test]
+    fn test_hmac_truncated_128() {
+        let full = hmac(b"ipsec packet", b"key");
+        let tag = hmac_truncated(b"ipsec packet", b"key", 16).unwrap();
+
+        assert_eq!(tag, full[..16]);
+        assert!(verify_hmac_truncated(b"ipsec packet", &tag, b"key", 16));
+        assert!(!verify_hmac_truncated(b"ipsec packet", &full, b"key", 16));
+        assert!(!verify_hmac_truncated(b"ipsec packet", &tag[..15], b"key", 16));
+        assert!(!verify_hmac_truncated(b"other packet", &tag, b"key", 16));
+    }
+
+    #[test]
+    fn test_hmac_truncated_length_errors() {
+        assert_eq!(hmac_truncated(b"data", b"key", 32).unwrap(), hmac(b"data", b"key"));
+        assert_eq!(hmac_truncated(b"data", b"key", 33), Err(Error::TagLength { len: 33 }));
+        assert_eq!(hmac_truncated(b"data", b"key", 0), Err(Error::TagLength { len: 0 }));
+        assert!(!verify_hmac_truncated(b"data", &[], b"key", 0));
+    }
+
+    #[test]
+    fn test_verify_hmac_hex() {
+        let tag = hmac(b"message", b"key");
+        let tag_hex = hex::encode(&tag);
+        let mut wrong = tag.clone();
+        wrong[31] ^= 1;
+
+        assert_eq!(verify_hmac_hex(b"message", &tag_hex, b"key"), Ok(true));
+        assert_eq!(verify_hmac_hex(b"message", &tag_hex.to_uppercase(), b"key"), Ok(true));
+        assert_eq!(verify_hmac_hex(b"message", &hex::encode(&wrong), b"key"), Ok(false));
+        assert_eq!(
+            verify_hmac_hex(b"message", &tag_hex[..62], b"key"),
+            Err(VerifyError::TagLength { expected: 32, got: 31 })
+        );
+        assert_eq!(
+            verify_hmac_hex(b"message", &tag_hex[..63], b"key"),
+            Err(VerifyError::Encoding(HexError::OddLength { len: 63 }))
+        );
+        let malformed = format!("zz{}", &tag_hex[2..]);
+        assert_eq!(
+            verify_hmac_hex(b"message", &malformed, b"key"),
+            Err(VerifyError::Encoding(HexError::InvalidChar { index: 0, ch: 'z' }))
+        );
+    }
+
+    #[test]
+    fn test_verify_hmac_checked() {
+        let tag = hmac(b"message", b"key");
+        let mut wrong = tag.clone();
+        wrong[0] ^= 1;
+
+        assert_eq!(verify_hmac_checked(b"message", &tag, b"key"), Ok(true));
+        assert_eq!(verify_hmac_checked(b"message", &wrong, b"key"), Ok(false));
+        assert_eq!(
+            verify_hmac_checked(b"message", &tag[..31], b"key"),
+            Err(VerifyError::TagLength { expected: 32, got: 31 })
+        );
+        assert_eq!(
+            verify_hmac_checked(b"message", &[tag.as_slice(), &tag].concat(), b"key"),
+            Err(VerifyError::TagLength { expected: 32, got: 64 })
+        );
+        assert!(!verify_hmac(b"message", &tag[..31], b"key"));
+    }
+
+    #[test]
+    fn test_export_and_resume_state() {
+        let data = vec![0x77_u8; 300];
+        let mut hasher = Sha256::new();
+        hasher.update(&data[..128]);
+
+        let state = hasher.export_state().unwrap();
+        assert_eq!(state.total_len, 128);
+
+        let mut resumed = Sha256::from_state(state).unwrap();
+        resumed.update(&data[128..]);
+        assert_eq!(resumed.finalize(), hash(&data));
+
+        hasher.update(&data[128..130]);
+        assert_eq!(hasher.export_state(), Err(Error::PartialBlock { buffered: 2 }));
+    }
+
+    // A resumed state stands in for the 2^61 bytes no test could feed
+    #[test]
+    fn test_try_update_length_limit() {
+        let near_limit = Sha256State { state: INITIAL_HASH, total_len: MAX_INPUT_LEN - 10 };
+        let mut hasher = Sha256::from_state(near_limit).unwrap();
+
+        assert_eq!(hasher.try_update(&[0; 11]), Err(HashError::InputTooLong { len: MAX_INPUT_LEN + 1 }));
+        assert_eq!(hasher.try_update(&[0; 10]), Ok(()));
+        assert_eq!(hasher.try_update(&[0]), Err(HashError::InputTooLong { len: MAX_INPUT_LEN + 1 }));
+        assert_eq!(hasher.finalize().len(), 32);
+
+        let past_limit = Sha256State { state: INITIAL_HASH, total_len: MAX_INPUT_LEN + 1 };
+        assert!(matches!(Sha256::from_state(past_limit), Err(HashError::InputTooLong { .. })));
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the SHA-256 limit")]
+    fn test_update_past_length_limit_panics() {
+        let near_limit = Sha256State { state: INITIAL_HASH, total_len: MAX_INPUT_LEN };
+        Sha256::from_state(near_limit).unwrap().update(b"x");
     }
 }
\ No newline at end of file
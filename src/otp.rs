@@ -0,0 +1,89 @@
+//! One-time passwords: HOTP (RFC 4226) and TOTP (RFC 6238) over HMAC-SHA256.
+
+use crate::error::Error;
+use crate::hmac_fixed;
+
+/// Computes the HOTP value for `counter`, `digits` decimal digits long.
+///
+/// The big-endian counter is MACed under `key`, four bytes are picked at the
+/// offset given by the low nibble of the last tag byte, their top bit is
+/// cleared, and the result is reduced modulo `10^digits`. Fails with
+/// `Error::OtpDigits` unless `digits` is between 6 and 8.
+pub fn hotp(key: &[u8], counter: u64, digits: u32) -> Result<u32, Error> {
+    if !(6..=8).contains(&digits) {
+        return Err(Error::OtpDigits { digits });
+    }
+    let tag = hmac_fixed(&counter.to_be_bytes(), key);
+    Ok(dynamic_truncate(&tag) % 10_u32.pow(digits))
+}
+
+/// Computes the TOTP value at `unix_time`, as HOTP of `unix_time / step_secs`.
+///
+/// Fails with `Error::OtpDigits` like `hotp`, and with `Error::ZeroTimeStep`
+/// if `step_secs` is zero. The usual step is 30 seconds.
+pub fn totp(key: &[u8], unix_time: u64, step_secs: u64, digits: u32) -> Result<u32, Error> {
+    if step_secs == 0 {
+        return Err(Error::ZeroTimeStep);
+    }
+    hotp(key, unix_time / step_secs, digits)
+}
+
+// Reads 31 bits big-endian at the offset named by the low nibble of the last byte.
+// The top bit is masked off so the value reads the same signed or unsigned
+fn dynamic_truncate(tag: &[u8; 32]) -> u32 {
+    let offset = (tag[31] & 0x0f) as usize;
+    let word = u32::from_be_bytes([tag[offset], tag[offset + 1], tag[offset + 2], tag[offset + 3]]);
+    word & 0x7fff_ffff
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: &[u8] = b"12345678901234567890123456789012";
+
+    // RFC 6238 appendix B, SHA-256 column
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let vectors = [
+            (59, 46119246),
+            (1111111109, 68084774),
+            (1111111111, 67062674),
+            (1234567890, 91819424),
+            (2000000000, 90698825),
+            (20000000000, 77737706),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(totp(SEED, time, 30, 8), Ok(expected), "time {}", time);
+        }
+    }
+
+    #[test]
+    fn test_hotp_six_digits() {
+        assert_eq!(hotp(SEED, 0, 6), Ok(920136));
+        assert_eq!(hotp(SEED, 1, 6), Ok(119246));
+        assert_eq!(hotp(SEED, 2, 6), Ok(882438));
+    }
+
+    #[test]
+    fn test_dynamic_truncation_masks_high_bit() {
+        // Offset 10 points at bytes with the top bit set
+        let mut tag = [0_u8; 32];
+        tag[31] = 0x0a;
+        tag[10..14].copy_from_slice(&[0xff, 0xee, 0xdd, 0xcc]);
+        assert_eq!(dynamic_truncate(&tag), 0x7fee_ddcc);
+
+        // Offset 15 reads into the last byte itself
+        tag[31] = 0xff;
+        tag[15..19].copy_from_slice(&[0x80, 0x00, 0x00, 0x01]);
+        assert_eq!(dynamic_truncate(&tag), 0x0000_0001);
+    }
+
+    #[test]
+    fn test_otp_parameter_errors() {
+        assert_eq!(hotp(SEED, 0, 5), Err(Error::OtpDigits { digits: 5 }));
+        assert_eq!(hotp(SEED, 0, 9), Err(Error::OtpDigits { digits: 9 }));
+        assert_eq!(totp(SEED, 59, 0, 8), Err(Error::ZeroTimeStep));
+    }
+}
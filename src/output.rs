@@ -0,0 +1,395 @@
+use alloc::{string::String, vec::Vec};
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+
+use crate::error::Error;
+use crate::utils::ct_equal;
+
+/// A SHA-256 digest held by value.
+///
+/// `Display` and `{:x}` print the lowercase hex of the digest and `{:X}` the
+/// uppercase. Equality is checked in constant time, so comparing a computed
+/// digest against an expected one does not leak where they differ.
+#[derive(Clone, Copy, Debug)]
+pub struct Digest([u8; 32]);
+
+impl Digest {
+    // Length of the abbreviated form, as used by git
+    pub const DEFAULT_SHORT_LEN: usize = 7;
+
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Digest(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    // Returns the first nibbles lowercase hex characters of the digest, at most all 64
+    pub fn short(&self, nibbles: usize) -> String {
+        let mut hex = self.hex();
+        hex.truncate(nibbles.min(64));
+        hex
+    }
+
+    // Whether the hex form starts with prefix, ignoring case, for looking up abbreviations
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        prefix.len() <= 64 && self.hex().starts_with(&prefix.to_ascii_lowercase())
+    }
+
+    /// XORs the digests byte-wise into one order-independent value.
+    ///
+    /// This is an XOR accumulator, not a Merkle root: the result is the same in
+    /// any order, and XORing an element in a second time removes it again, so
+    /// membership can be added and revoked cheaply. It commits to nothing about
+    /// order or multiplicity, and an empty slice gives all zeros. The XOR of
+    /// digests is also malleable: anyone who can choose inputs can steer the
+    /// combined value with enough of them, so only combine digests of elements
+    /// an attacker doesn't control, or use a Merkle tree where that matters.
+    pub fn xor_combine(digests: &[Digest]) -> Digest {
+        let mut combined = [0_u8; 32];
+        for digest in digests {
+            for (c, b) in combined.iter_mut().zip(digest.0.iter()) {
+                *c ^= b;
+            }
+        }
+        Digest(combined)
+    }
+
+    fn hex(&self) -> String {
+        crate::hex::encode(&self.0)
+    }
+}
+
+impl PartialEq for Digest {
+    fn eq(&self, other: &Self) -> bool {
+        ct_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for Digest {}
+
+// Hashes exactly like the byte slice, as the Borrow impl below requires
+impl Hash for Digest {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_slice().hash(state);
+    }
+}
+
+impl fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::UpperHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl From<[u8; 32]> for Digest {
+    fn from(bytes: [u8; 32]) -> Self {
+        Digest(bytes)
+    }
+}
+
+impl From<Digest> for [u8; 32] {
+    fn from(digest: Digest) -> Self {
+        digest.0
+    }
+}
+
+impl From<Digest> for Vec<u8> {
+    fn from(digest: Digest) -> Self {
+        digest.0.to_vec()
+    }
+}
+
+// Accepts the Vec returned by hash(). Anything but 32 bytes is a LengthMismatch
+impl TryFrom<Vec<u8>> for Digest {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Error> {
+        let got = bytes.len();
+        bytes.try_into().map(Digest).map_err(|_| Error::LengthMismatch { expected: 32, got })
+    }
+}
+
+impl AsRef<[u8; 32]> for Digest {
+    fn as_ref(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Hashes and compares exactly like the underlying byte slice, so a Digest key
+// can be looked up by &[u8] and vice versa
+impl Borrow<[u8]> for Digest {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// Human-readable formats such as JSON get the lowercase hex string, binary
+// formats such as bincode the raw 32 bytes
+#[cfg(feature = "serde")]
+impl serde::Serialize for Digest {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.hex())
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Digest {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(DigestVisitor)
+        } else {
+            deserializer.deserialize_bytes(DigestVisitor)
+        }
+    }
+}
+
+// Accepts exactly 64 hex characters or exactly 32 bytes, the latter either as a
+// byte string or as a sequence of 32 u8 elements
+#[cfg(feature = "serde")]
+struct DigestVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for DigestVisitor {
+    type Value = Digest;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a SHA-256 digest as 64 hex characters or 32 bytes")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, hex: &str) -> Result<Digest, E> {
+        if hex.len() != 64 {
+            return Err(E::invalid_length(hex.len(), &self));
+        }
+        let bytes = crate::hex::decode(hex).map_err(E::custom)?;
+        self.visit_bytes(&bytes)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Digest, E> {
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| E::invalid_length(bytes.len(), &self))?;
+        Ok(Digest(bytes))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Digest, A::Error> {
+        let mut bytes = [0_u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq.next_element()?.ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+        }
+
+        // Count any trailing elements so the error reports the real length
+        let mut len = 32;
+        while seq.next_element::<u8>()?.is_some() {
+            len += 1;
+        }
+        if len != 32 {
+            return Err(serde::de::Error::invalid_length(len, &self));
+        }
+        Ok(Digest(bytes))
+    }
+}
+
+// An HMAC-SHA256 tag held by value. Equality is checked in constant time
+#[derive(Clone, Copy, Debug)]
+pub struct Tag([u8; 32]);
+
+impl Tag {
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Tag(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl PartialEq for Tag {
+    fn eq(&self, other: &Self) -> bool {
+        ct_equal(&self.0, &other.0)
+    }
+}
+
+impl Eq for Tag {}
+
+impl From<[u8; 32]> for Tag {
+    fn from(bytes: [u8; 32]) -> Self {
+        Tag(bytes)
+    }
+}
+
+impl From<Tag> for [u8; 32] {
+    fn from(tag: Tag) -> Self {
+        tag.0
+    }
+}
+
+impl From<Tag> for Vec<u8> {
+    fn from(tag: Tag) -> Self {
+        tag.0.to_vec()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+    use crate::hex::encode;
+    use std::collections::HashMap;
+
+    fn sample() -> ([u8; 32], Digest) {
+        let bytes: [u8; 32] = hash(b"digest conversions").try_into().unwrap();
+        (bytes, Digest::from(bytes))
+    }
+
+    #[test]
+    fn test_digest_into_bytes() {
+        let (bytes, digest) = sample();
+
+        assert_eq!(<[u8; 32]>::from(digest), bytes);
+        assert_eq!(Vec::<u8>::from(digest), bytes.to_vec());
+        assert_eq!(AsRef::<[u8; 32]>::as_ref(&digest), &bytes);
+        assert_eq!(Digest::new(bytes), digest);
+    }
+
+    #[test]
+    fn test_digest_formatting() {
+        let (bytes, digest) = sample();
+        let full = encode(&bytes);
+
+        assert_eq!(format!("{}", digest), full);
+        assert_eq!(format!("{:x}", digest), full);
+        assert_eq!(format!("{:X}", digest), full.to_uppercase());
+        assert_eq!(Digest::try_from(hash(b"abc")).unwrap().to_string(),
+                   "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_digest_from_vec_and_eq() {
+        let (bytes, digest) = sample();
+
+        assert_eq!(Digest::try_from(bytes.to_vec()), Ok(digest));
+        assert_eq!(Digest::try_from(bytes[..31].to_vec()), Err(Error::LengthMismatch { expected: 32, got: 31 }));
+        assert_eq!(AsRef::<[u8]>::as_ref(&digest), bytes.as_slice());
+
+        let mut other = bytes;
+        other[31] ^= 1;
+        assert_ne!(Digest::new(other), digest);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_digest_serde_round_trip() {
+        let (bytes, digest) = sample();
+
+        let json = serde_json::to_string(&digest).unwrap();
+        assert_eq!(json, format!("\"{}\"", digest));
+        assert_eq!(serde_json::from_str::<Digest>(&json).unwrap(), digest);
+        assert_eq!(serde_json::from_str::<Digest>(&json.to_uppercase()).unwrap(), digest);
+
+        let encoded = bincode::serialize(&digest).unwrap();
+        assert_eq!(encoded[8..], bytes);
+        assert_eq!(bincode::deserialize::<Digest>(&encoded).unwrap(), digest);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_digest_deserialize_rejects_wrong_length() {
+        let (bytes, digest) = sample();
+        let hex = digest.to_string();
+
+        assert!(serde_json::from_str::<Digest>(&format!("\"{}\"", &hex[..62])).is_err());
+        assert!(serde_json::from_str::<Digest>(&format!("\"{}00\"", hex)).is_err());
+        assert!(serde_json::from_str::<Digest>(&format!("\"{}zz\"", &hex[..62])).is_err());
+
+        assert!(bincode::deserialize::<Digest>(&bincode::serialize(&bytes[..31]).unwrap()).is_err());
+        assert!(bincode::deserialize::<Digest>(&bincode::serialize(&[0_u8; 33][..]).unwrap()).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_digest_deserialize_from_seq() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+
+        let (bytes, digest) = sample();
+        let from_seq = |elements: &[u8]| {
+            Digest::deserialize(SeqDeserializer::<_, Error>::new(elements.iter().copied()))
+        };
+
+        assert_eq!(from_seq(&bytes).unwrap(), digest);
+        assert!(from_seq(&bytes[..31]).is_err());
+        assert!(from_seq(&[0_u8; 33]).is_err());
+    }
+
+    #[test]
+    fn test_digest_short() {
+        let (bytes, digest) = sample();
+        let full = encode(&bytes);
+
+        assert_eq!(digest.short(Digest::DEFAULT_SHORT_LEN), full[..7]);
+        assert_eq!(digest.short(0), "");
+        assert_eq!(digest.short(100), full);
+        assert!(digest.starts_with(&digest.short(7)));
+        assert!(digest.starts_with(&digest.short(12).to_uppercase()));
+        assert!(!digest.starts_with("not hex"));
+    }
+
+    #[test]
+    fn test_xor_combine() {
+        let digests: Vec<Digest> = [b"a".as_slice(), b"b", b"c"]
+            .iter()
+            .map(|data| Digest::from(<[u8; 32]>::try_from(hash(data)).unwrap()))
+            .collect();
+        let combined = Digest::xor_combine(&digests);
+
+        let reordered = [digests[2], digests[0], digests[1]];
+        assert_eq!(Digest::xor_combine(&reordered), combined);
+
+        // Adding an element a second time cancels it
+        let with_repeat = [digests[0], digests[1], digests[2], digests[1]];
+        assert_eq!(Digest::xor_combine(&with_repeat), Digest::xor_combine(&[digests[0], digests[2]]));
+        assert_eq!(Digest::xor_combine(&[]), Digest::new([0; 32]));
+    }
+
+    #[test]
+    fn test_digest_borrow_lookup() {
+        let (bytes, digest) = sample();
+
+        let mut by_digest: HashMap<Digest, &str> = HashMap::new();
+        by_digest.insert(digest, "found");
+        assert_eq!(by_digest.get(bytes.as_slice()), Some(&"found"));
+
+        let mut by_vec: HashMap<Vec<u8>, &str> = HashMap::new();
+        by_vec.insert(bytes.to_vec(), "found");
+        assert_eq!(by_vec.get::<[u8]>(digest.borrow()), Some(&"found"));
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::{Component, Path};
+
+use crate::Sha256;
+
+/// Hashes a path after lexical normalization, for use as a stable cache key.
+///
+/// Normalization works on the path's text only and never touches the filesystem:
+/// - repeated and trailing separators are ignored, so `a//b/` is `a/b`
+/// - `.` components are dropped, so `a/./b` is `a/b`
+/// - `..` removes the preceding normal component; it is dropped directly after
+///   a root, and kept when nothing precedes it, so `../a` stays distinct from `a`
+///
+/// A root or prefix is hashed too, so `/a` and `a` differ. Each remaining
+/// component is hashed as its OS-string bytes preceded by their length as a
+/// big-endian `u64`, so components can't run into each other.
+#[must_use]
+pub fn hash_path(path: &Path) -> [u8; 32] {
+    let mut components: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    for component in components {
+        let bytes = component.as_os_str().as_encoded_bytes();
+        hasher.update(&(bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    }
+    hasher.finish()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equivalent_paths() {
+        let expected = hash_path(Path::new("a/b"));
+
+        assert_eq!(hash_path(Path::new("a//b")), expected);
+        assert_eq!(hash_path(Path::new("a/./b")), expected);
+        assert_eq!(hash_path(Path::new("./a/b/")), expected);
+        assert_eq!(hash_path(Path::new("a/c/../b")), expected);
+        assert_eq!(hash_path(Path::new("/../a")), hash_path(Path::new("/a")));
+    }
+
+    #[test]
+    fn test_distinct_paths() {
+        let paths = ["a/b", "/a/b", "ab", "a/b/c", "b/a", "../a/b", "a"];
+
+        for (i, a) in paths.iter().enumerate() {
+            for b in &paths[i + 1..] {
+                assert_ne!(hash_path(Path::new(a)), hash_path(Path::new(b)), "{} and {}", a, b);
+            }
+        }
+    }
+}
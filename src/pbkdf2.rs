@@ -0,0 +1,103 @@
+//! PBKDF2-HMAC-SHA256 password-based key derivation (RFC 8018, formerly RFC 2898).
+
+use alloc::vec::Vec;
+
+use crate::error::KdfError;
+use crate::Hmac;
+
+/// Largest derived key PBKDF2 can produce: `(2^32 - 1)` blocks of 32 bytes, as the
+/// block index is a `u32`.
+pub const MAX_OUTPUT_LEN: u64 = u32::MAX as u64 * 32;
+
+/// Derives `dk_len` bytes from `password` and `salt` with `iterations` rounds.
+///
+/// Each 32-byte block i is F(i) = U_1 ^ U_2 ^ ... ^ U_c, where
+/// U_1 = HMAC(password, salt || i) with i as a big-endian `u32` starting at 1,
+/// and U_j = HMAC(password, U_{j-1}). The password is normalized once and the
+/// keyed context cloned for every HMAC. Fails with `KdfError::ZeroIterations`
+/// if `iterations` is zero, and with `KdfError::OutputTooLong` if `dk_len`
+/// exceeds `MAX_OUTPUT_LEN`.
+pub fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32, dk_len: usize) -> Result<Vec<u8>, KdfError> {
+    if iterations == 0 {
+        return Err(KdfError::ZeroIterations);
+    }
+    // Only reachable with a 64-bit usize, where the limit fits
+    if dk_len as u64 > MAX_OUTPUT_LEN {
+        return Err(KdfError::OutputTooLong { requested: dk_len, max: MAX_OUTPUT_LEN as usize });
+    }
+
+    let prf = Hmac::new(password);
+    let mut derived = Vec::with_capacity(dk_len);
+    let mut block_index = 1_u32;
+    while derived.len() < dk_len {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&block_index.to_be_bytes());
+        let mut u: [u8; 32] = mac.finish();
+        let mut block = u;
+
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finish();
+            for (b, x) in block.iter_mut().zip(u.iter()) {
+                *b ^= x;
+            }
+        }
+
+        let take = (dk_len - derived.len()).min(block.len());
+        derived.extend_from_slice(&block[..take]);
+        block_index += 1;
+    }
+    Ok(derived)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode;
+
+    #[test]
+    fn test_pbkdf2_vectors() {
+        assert_eq!(
+            encode(&pbkdf2(b"password", b"salt", 1, 32).unwrap()),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(
+            encode(&pbkdf2(b"password", b"salt", 4096, 32).unwrap()),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+        assert_eq!(
+            encode(&pbkdf2(b"pass\0word", b"sa\0lt", 4096, 16).unwrap()),
+            "89b69d0516f829893c696226650a8687"
+        );
+    }
+
+    // A derived key longer than one block
+    #[test]
+    fn test_pbkdf2_multiple_blocks() {
+        let dk = pbkdf2(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, 40).unwrap();
+        assert_eq!(
+            encode(&dk),
+            "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1c635518c7dac47e9"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_rejects_zero_iterations() {
+        assert_eq!(pbkdf2(b"password", b"salt", 0, 32), Err(KdfError::ZeroIterations));
+    }
+
+    // Checked before anything is allocated or derived, so this returns immediately
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_pbkdf2_output_length_limit() {
+        let over = MAX_OUTPUT_LEN as usize + 1;
+        assert_eq!(
+            pbkdf2(b"password", b"salt", 1, over),
+            Err(KdfError::OutputTooLong { requested: over, max: MAX_OUTPUT_LEN as usize })
+        );
+        assert_eq!(MAX_OUTPUT_LEN, 137_438_953_440);
+    }
+}
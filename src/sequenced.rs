@@ -0,0 +1,91 @@
+use alloc::vec::Vec;
+
+use crate::utils::ct_equal;
+use crate::Hmac;
+
+/// HMAC over a stream of messages with built-in sequence numbers.
+///
+/// Each tag is `hmac(seq || message, key)` where `seq` is the message's sequence
+/// number as an 8-byte big-endian integer. `sign` hands out 0, 1, 2, ... in order.
+/// `verify` only accepts a sequence number strictly greater than the last one it
+/// accepted, so replayed and reordered messages are rejected.
+pub struct SequencedHmac {
+    mac: Hmac,
+    next_seq: u64,
+    last_verified: Option<u64>,
+}
+
+impl SequencedHmac {
+    pub fn new(key: &[u8]) -> Self {
+        SequencedHmac {
+            mac: Hmac::new(key),
+            next_seq: 0,
+            last_verified: None,
+        }
+    }
+
+    // Tags the message with the next sequence number and returns both
+    #[must_use]
+    pub fn sign(&mut self, message: &[u8]) -> (u64, Vec<u8>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        (seq, self.tag(seq, message))
+    }
+
+    // Checks the tag in constant time and that seq moves forward. Only a valid
+    // message advances the last verified sequence number
+    #[must_use]
+    pub fn verify(&mut self, seq: u64, message: &[u8], tag: &[u8]) -> bool {
+        let valid_tag = ct_equal(&self.tag(seq, message), tag);
+        let fresh = self.last_verified.is_none_or(|last| seq > last);
+
+        if valid_tag && fresh {
+            self.last_verified = Some(seq);
+            return true;
+        }
+        false
+    }
+
+    fn tag(&self, seq: u64, message: &[u8]) -> Vec<u8> {
+        let mut mac = self.mac.clone();
+        mac.update(&seq.to_be_bytes());
+        mac.update(message);
+        mac.finalize()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hmac;
+
+    #[test]
+    fn test_sequenced_framing() {
+        let mut sender = SequencedHmac::new(b"stream key");
+        let (seq, tag) = sender.sign(b"first");
+
+        assert_eq!(seq, 0);
+        assert_eq!(tag, hmac([&0_u64.to_be_bytes()[..], b"first"].concat(), b"stream key"));
+        assert_eq!(sender.sign(b"second").0, 1);
+    }
+
+    #[test]
+    fn test_sequenced_rejects_replay_and_reorder() {
+        let mut sender = SequencedHmac::new(b"stream key");
+        let mut receiver = SequencedHmac::new(b"stream key");
+        let m0 = sender.sign(b"zero");
+        let m1 = sender.sign(b"one");
+        let m2 = sender.sign(b"two");
+
+        assert!(receiver.verify(m0.0, b"zero", &m0.1));
+        assert!(receiver.verify(m2.0, b"two", &m2.1));
+
+        // Replay of an accepted message, and a late message from before it
+        assert!(!receiver.verify(m2.0, b"two", &m2.1));
+        assert!(!receiver.verify(m1.0, b"one", &m1.1));
+
+        // A tag moved to a different sequence number doesn't verify either
+        assert!(!receiver.verify(3, b"one", &m1.1));
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::Sha256;
+
+/// Hashes the `bincode` encoding of `value` without buffering it.
+///
+/// The serializer writes straight into the hasher through its `io::Write`
+/// impl, so the digest equals `hash()` of `bincode::serialize(value)` while the
+/// encoded bytes are never held in memory at once.
+pub fn hash_serialized<T: Serialize>(value: &T) -> Result<[u8; 32], Error> {
+    let mut hasher = Sha256::new();
+    bincode::serialize_into(&mut hasher, value).map_err(|e| Error::Serialization(e.to_string()))?;
+    Ok(hasher.finish())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[derive(Serialize)]
+    struct Record {
+        id: u64,
+        name: String,
+        tags: Vec<String>,
+        payload: Vec<u8>,
+    }
+
+    #[test]
+    fn test_hash_serialized_matches_bincode_bytes() {
+        let record = Record {
+            id: 7,
+            name: "bernie".to_string(),
+            tags: vec!["a".to_string(), "bc".to_string()],
+            payload: (0..200).map(|i| i as u8).collect(),
+        };
+        let bytes = bincode::serialize(&record).unwrap();
+
+        assert_eq!(hash_serialized(&record).unwrap().to_vec(), hash(&bytes));
+    }
+}
@@ -0,0 +1,86 @@
+use alloc::vec::Vec;
+use zeroize::Zeroizing;
+
+use crate::constants::{BLOCKSIZE, INITIAL_HASH_224};
+use crate::{state_to_bytes, xor_pads, Sha256};
+
+// SHA-224 output length in bytes
+const OUTPUT_LEN: usize = 28;
+
+// SHA-224 runs the SHA-256 compression from its own initial hash value
+fn sha224() -> Sha256 {
+    Sha256::resume(INITIAL_HASH_224, 0)
+}
+
+// Finishes a hasher started with the SHA-224 IV in place and keeps the first 28
+// bytes of the final state
+fn finish224(hasher: &mut Sha256) -> [u8; OUTPUT_LEN] {
+    let mut digest = [0_u8; OUTPUT_LEN];
+    digest.copy_from_slice(&state_to_bytes(&hasher.finish_in_place())[..OUTPUT_LEN]);
+    digest
+}
+
+#[must_use]
+pub fn hash224(data: &[u8]) -> Vec<u8> {
+    let mut hasher = sha224();
+    hasher.update(data);
+    finish224(&mut hasher).to_vec()
+}
+
+/// Computes HMAC-SHA-224 as in RFC 4231.
+///
+/// The block size is the same 64 bytes as SHA-256, but a key longer than a
+/// block is first hashed with SHA-224, and the inner digest fed to the outer
+/// hash is 28 bytes.
+#[must_use]
+pub fn hmac224(data: &[u8], key: &[u8]) -> Vec<u8> {
+    // Everything derived from the key is wiped on drop, as in the SHA-256 Hmac
+    let mut normalized_key = Zeroizing::new([0_u8; BLOCKSIZE]);
+    if key.len() > BLOCKSIZE {
+        let mut hasher = Zeroizing::new(sha224());
+        hasher.update(key);
+        normalized_key[..OUTPUT_LEN].copy_from_slice(&Zeroizing::new(finish224(&mut hasher))[..]);
+    } else {
+        normalized_key[..key.len()].copy_from_slice(key);
+    }
+    let (inner_key, outer_key) = xor_pads(&*normalized_key);
+    let (inner_key, outer_key) = (Zeroizing::new(inner_key), Zeroizing::new(outer_key));
+
+    let mut inner = Zeroizing::new(sha224());
+    inner.update(&*inner_key);
+    inner.update(data);
+    let inner_hash = Zeroizing::new(finish224(&mut inner));
+
+    let mut outer = Zeroizing::new(sha224());
+    outer.update(&*outer_key);
+    outer.update(&*inner_hash);
+    finish224(&mut outer).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode;
+
+    #[test]
+    fn test_hash224_nist_vectors() {
+        assert_eq!(encode(&hash224(b"")), "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f");
+        assert_eq!(encode(&hash224(b"abc")), "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7");
+        assert_eq!(
+            encode(&hash224(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+        );
+    }
+
+    #[test]
+    fn test_hmac224_rfc4231_vectors() {
+        assert_eq!(
+            encode(&hmac224(b"Hi There", &[0x0b; 20])),
+            "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22"
+        );
+        assert_eq!(
+            encode(&hmac224(b"Test Using Larger Than Block-Size Key - Hash Key First", &[0xaa; 131])),
+            "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use alloc::vec::Vec;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::constants::{BLOCKSIZE_512, INITIAL_HASH_512, INITIAL_HASH_512_256, ROUND_CONSTANTS_512};
+use crate::utils::{ch64, maj64, sigma_512_0, sigma_512_1, Sigma_512_0, Sigma_512_1};
+
+// SHA-512 output length in bytes
+const OUTPUT_LEN: usize = 64;
+
+// SHA-512/256 output length in bytes
+const OUTPUT_LEN_256: usize = 32;
+
+// Incremental SHA-512, laid out like Sha256 but with 64-bit words, 128-byte
+// blocks and a 128-bit length field
+#[derive(Clone)]
+struct Sha512 {
+    state: [u64; 8],
+    buffer: [u8; BLOCKSIZE_512],
+    buffer_len: usize,
+    total_len: u128,
+}
+
+impl Sha512 {
+    fn with_iv(state: [u64; 8]) -> Self {
+        Sha512 {
+            state,
+            buffer: [0_u8; BLOCKSIZE_512],
+            buffer_len: 0,
+            total_len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u128;
+
+        // Top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (BLOCKSIZE_512 - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < BLOCKSIZE_512 {
+                return;
+            }
+            compress512(&mut self.state, &parse_block512(&self.buffer));
+            self.buffer_len = 0;
+        }
+
+        // Compress whole blocks straight out of the input
+        let mut blocks = data.chunks_exact(BLOCKSIZE_512);
+        for block in &mut blocks {
+            compress512(&mut self.state, &parse_block512(block));
+        }
+
+        // Keep the remainder for the next call
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+    }
+
+    // Pads the buffered tail in place and returns the full 64-byte serialized state.
+    // Working in place leaves no unwiped copy of a keyed state behind
+    fn finish(&mut self) -> [u8; OUTPUT_LEN] {
+        let bit_len = self.total_len * 8;
+
+        // Append a '1' bit followed by zeros
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+
+        // If there is no room left for the 128-bit length, it spills into another block
+        if self.buffer_len >= BLOCKSIZE_512 - 16 {
+            compress512(&mut self.state, &parse_block512(&self.buffer));
+            self.buffer.fill(0);
+        }
+
+        self.buffer[BLOCKSIZE_512 - 16..].copy_from_slice(&bit_len.to_be_bytes());
+        compress512(&mut self.state, &parse_block512(&self.buffer));
+
+        let mut digest = [0_u8; OUTPUT_LEN];
+        for (chunk, word) in digest.chunks_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+impl Zeroize for Sha512 {
+    fn zeroize(&mut self) {
+        self.state.zeroize();
+        self.buffer.zeroize();
+        self.buffer_len.zeroize();
+        self.total_len.zeroize();
+    }
+}
+
+// Parses a 128-byte chunk into a block of 16 big-endian u64's
+fn parse_block512(chunk: &[u8]) -> [u64; 16] {
+    let mut block = [0_u64; 16];
+    for (word, bytes) in block.iter_mut().zip(chunk.chunks_exact(8)) {
+        *word = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+    block
+}
+
+// Runs the 80 rounds of SHA-512 compression on one parsed block
+fn compress512(state: &mut [u64; 8], block: &[u64; 16]) {
+    // Initialize the message schedule
+    let mut message_schedule = [0_u64; 80];
+    message_schedule[..16].copy_from_slice(block);
+    for t in 16..80 {
+        message_schedule[t] = sigma_512_1(message_schedule[t - 2])
+                                        .wrapping_add(message_schedule[t - 7])
+                                        .wrapping_add(sigma_512_0(message_schedule[t - 15]))
+                                        .wrapping_add(message_schedule[t - 16]);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..80 {
+        let t1 = h.wrapping_add(Sigma_512_1(e))
+                  .wrapping_add(ch64(e, f, g))
+                  .wrapping_add(ROUND_CONSTANTS_512[t])
+                  .wrapping_add(message_schedule[t]);
+        let t2 = Sigma_512_0(a).wrapping_add(maj64(a, b, c));
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    // Update the hash value
+    for (word, temp_value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(temp_value);
+    }
+}
+
+/// Computes the 64-byte SHA-512 digest of `data` (FIPS 180-4).
+#[must_use]
+pub fn hash512(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha512::with_iv(INITIAL_HASH_512);
+    hasher.update(data);
+    hasher.finish().to_vec()
+}
+
+/// Computes SHA-512/256: SHA-512 from its own initial hash value, truncated to 32 bytes.
+///
+/// The different IV keeps it from being a simple prefix of `hash512`, and it is
+/// not the same digest as `hash()` despite the equal length.
+#[must_use]
+pub fn hash512_256(data: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha512::with_iv(INITIAL_HASH_512_256);
+    hasher.update(data);
+    hasher.finish()[..OUTPUT_LEN_256].to_vec()
+}
+
+/// Computes HMAC-SHA-512 as in RFC 4231.
+///
+/// The key block is SHA-512's 128 bytes, so only keys longer than that are
+/// hashed first, and the tag is 64 bytes.
+#[must_use]
+pub fn hmac512(data: &[u8], key: &[u8]) -> Vec<u8> {
+    // Everything derived from the key is wiped on drop, as in the SHA-256 Hmac
+    let mut normalized_key = Zeroizing::new([0_u8; BLOCKSIZE_512]);
+    if key.len() > BLOCKSIZE_512 {
+        let mut hasher = Zeroizing::new(Sha512::with_iv(INITIAL_HASH_512));
+        hasher.update(key);
+        normalized_key[..OUTPUT_LEN].copy_from_slice(&Zeroizing::new(hasher.finish())[..]);
+    } else {
+        normalized_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner_key = Zeroizing::new([0x36_u8; BLOCKSIZE_512]);
+    let mut outer_key = Zeroizing::new([0x5c_u8; BLOCKSIZE_512]);
+    for (i, &k) in normalized_key.iter().enumerate() {
+        inner_key[i] ^= k;
+        outer_key[i] ^= k;
+    }
+
+    let mut inner = Zeroizing::new(Sha512::with_iv(INITIAL_HASH_512));
+    inner.update(&*inner_key);
+    inner.update(data);
+    let inner_hash = Zeroizing::new(inner.finish());
+
+    let mut outer = Zeroizing::new(Sha512::with_iv(INITIAL_HASH_512));
+    outer.update(&*outer_key);
+    outer.update(&*inner_hash);
+    outer.finish().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode;
+
+    const MESSAGE_896: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn test_hash512_nist_vectors() {
+        assert_eq!(
+            encode(&hash512(b"")),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            encode(&hash512(b"abc")),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            encode(&hash512(MESSAGE_896)),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+    }
+
+    #[test]
+    fn test_hash512_256_nist_vectors() {
+        assert_eq!(encode(&hash512_256(b"")), "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a");
+        assert_eq!(encode(&hash512_256(b"abc")), "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23");
+        assert_eq!(encode(&hash512_256(MESSAGE_896)), "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a");
+    }
+
+    // Lengths around the 112-byte point where the 128-bit length field spills over
+    #[test]
+    fn test_hash512_block_boundaries() {
+        let cases = [
+            (111, "421318daeb8461d4"),
+            (112, "efa85a2ad32eee7c"),
+            (127, "84d778b759460c82"),
+            (128, "ed24df3079846053"),
+            (129, "76fe07b95acb0e53"),
+            (300, "630dcc8c2d0d9c76"),
+        ];
+        for (len, prefix) in cases {
+            let data = vec![0x5a_u8; len];
+            assert_eq!(encode(&hash512(&data)[..8]), prefix, "length {}", len);
+
+            let mut hasher = Sha512::with_iv(INITIAL_HASH_512);
+            for chunk in data.chunks(37) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finish().to_vec(), hash512(&data));
+        }
+    }
+
+    #[test]
+    fn test_hmac512_rfc4231_vectors() {
+        assert_eq!(
+            encode(&hmac512(b"Hi There", &[0x0b; 20])),
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854"
+        );
+        assert_eq!(
+            encode(&hmac512(b"Test Using Larger Than Block-Size Key - Hash Key First", &[0xaa; 131])),
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+
+use crate::output::{Digest, Tag};
+use crate::{Hmac, Sha256};
+
+// Size of the chunks readers are consumed in
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+// Reads the reader to the end, handing each chunk to sink
+fn feed_reader<R: Read>(reader: &mut R, mut sink: impl FnMut(&[u8])) -> io::Result<()> {
+    let mut chunk = [0_u8; READ_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(()),
+            Ok(n) => sink(&chunk[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Feeds everything the reader yields into a fresh hasher
+fn hasher_from_reader<R: Read>(reader: &mut R) -> io::Result<Sha256> {
+    let mut hasher = Sha256::new();
+    feed_reader(reader, |chunk| hasher.update(chunk))?;
+    Ok(hasher)
+}
+
+// Feeds everything the reader yields into an HMAC keyed with key
+fn mac_from_reader<R: Read>(reader: &mut R, key: &[u8]) -> io::Result<Hmac> {
+    let mut mac = Hmac::new(key);
+    feed_reader(reader, |chunk| mac.update(chunk))?;
+    Ok(mac)
+}
+
+// Hashes everything the reader yields, 8 KiB at a time. Short reads are fine; only
+// a read of zero bytes ends the input
+pub fn hash_reader<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    Ok(hasher_from_reader(reader)?.finalize())
+}
+
+// Hashes the contents of a file without loading it into memory
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    hash_reader(&mut File::open(path)?)
+}
+
+// Computes the HMAC of the contents of a file without loading it into memory
+pub fn hmac_file<P: AsRef<Path>>(path: P, key: &[u8]) -> io::Result<Vec<u8>> {
+    Ok(mac_from_reader(&mut File::open(path)?, key)?.finalize())
+}
+
+/// Hashes the contents of a file into a `Digest`.
+///
+/// The bytes are the same as `hash_file` returns, without the `Vec`.
+pub fn hash_file_digest<P: AsRef<Path>>(path: P) -> io::Result<Digest> {
+    Ok(Digest::from(hasher_from_reader(&mut File::open(path)?)?.finish()))
+}
+
+/// Computes the HMAC of the contents of a file as a `Tag`.
+///
+/// The bytes are the same as `hmac_file` returns, without the `Vec`.
+pub fn hmac_file_digest<P: AsRef<Path>>(path: P, key: &[u8]) -> io::Result<Tag> {
+    Ok(Tag::from(mac_from_reader(&mut File::open(path)?, key)?.finish()))
+}
+
+/// Hashes a reader that must yield exactly `expected_len` bytes.
+///
+/// A reader that ends early fails with `UnexpectedEof`, and one with more data
+/// fails with `InvalidData`. Reading stops one byte past `expected_len`, so an
+/// over-long or endless reader is never consumed further than that.
+pub fn hash_reader_exact<R: Read>(reader: &mut R, expected_len: u64) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut total: u64 = 0;
+    feed_reader(&mut reader.take(expected_len.saturating_add(1)), |chunk| {
+        hasher.update(chunk);
+        total += chunk.len() as u64;
+    })?;
+
+    if total < expected_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("expected {} bytes, reader ended after {}", expected_len, total),
+        ));
+    }
+    if total > expected_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {} bytes, reader has more", expected_len),
+        ));
+    }
+    Ok(hasher.finish())
+}
+
+// Lets the hasher be the target of io::copy, write! and friends
+impl Write for Sha256 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A hashing `Write` sink that refuses more than `limit` bytes.
+///
+/// A write that would push the total over the limit fails without being hashed,
+/// and the writer is then poisoned so `finalize` reports the error too.
+pub struct LimitedHashingWriter {
+    hasher: Sha256,
+    limit: u64,
+    written: u64,
+    exceeded: bool,
+}
+
+impl LimitedHashingWriter {
+    pub fn new(limit: u64) -> Self {
+        LimitedHashingWriter {
+            hasher: Sha256::new(),
+            limit,
+            written: 0,
+            exceeded: false,
+        }
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    pub fn finalize(self) -> io::Result<Vec<u8>> {
+        if self.exceeded {
+            return Err(limit_error(self.limit));
+        }
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl Write for LimitedHashingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.exceeded || self.written + buf.len() as u64 > self.limit {
+            self.exceeded = true;
+            return Err(limit_error(self.limit));
+        }
+        self.hasher.update(buf);
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn limit_error(limit: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("hashing limit of {} bytes exceeded", limit))
+}
+
+// Hashes every chunk received on the channel, in order, until all senders hang up
+pub fn hash_channel(rx: Receiver<Vec<u8>>) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    for chunk in rx {
+        hasher.update(&chunk);
+    }
+    hasher.finalize()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_hash_channel() {
+        let chunks: Vec<Vec<u8>> = (0..20_u8).map(|i| vec![i; 10 + 7 * i as usize]).collect();
+        let expected = hash(chunks.concat());
+
+        let (tx, rx) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            for chunk in chunks {
+                tx.send(chunk).unwrap();
+            }
+        });
+
+        assert_eq!(hash_channel(rx), expected);
+        producer.join().unwrap();
+    }
+
+    // Hands out at most three bytes per read
+    struct TrickleReader<'a>(&'a [u8]);
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_hash_reader() {
+        let data: Vec<u8> = (0..20_000).map(|i| (i % 251) as u8).collect();
+
+        assert_eq!(hash_reader(&mut io::Cursor::new(&data)).unwrap(), hash(&data));
+        assert_eq!(hash_reader(&mut TrickleReader(&data[..1000])).unwrap(), hash(&data[..1000]));
+    }
+
+    #[test]
+    fn test_hash_reader_exact() {
+        let data = vec![0x3c_u8; 10_000];
+
+        let digest = hash_reader_exact(&mut data.as_slice(), 10_000).unwrap();
+        assert_eq!(digest.to_vec(), hash(&data));
+
+        let short = hash_reader_exact(&mut data.as_slice(), 10_001).unwrap_err();
+        assert_eq!(short.kind(), io::ErrorKind::UnexpectedEof);
+
+        let long = hash_reader_exact(&mut data.as_slice(), 9_999).unwrap_err();
+        assert_eq!(long.kind(), io::ErrorKind::InvalidData);
+
+        // An endless reader is cut off right after the expected length
+        let endless = hash_reader_exact(&mut io::repeat(1), 100).unwrap_err();
+        assert_eq!(endless.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_file_digest_matches_file_hash() {
+        let path = std::env::temp_dir().join(format!("bernie_hmac_stream_{}", std::process::id()));
+        let data: Vec<u8> = (0..20_000).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        assert_eq!(Vec::from(hash_file_digest(&path).unwrap()), hash_file(&path).unwrap());
+        assert_eq!(Vec::from(hmac_file_digest(&path, b"key").unwrap()), hmac_file(&path, b"key").unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_sha256_write() {
+        let mut hasher = Sha256::new();
+        let name = "There";
+        write!(hasher, "Hi {}", name).unwrap();
+
+        assert_eq!(hasher.finalize(), hash(b"Hi There"));
+    }
+
+    #[test]
+    fn test_limited_writer_within_limit() {
+        let data = vec![0x5a_u8; 100];
+        let mut writer = LimitedHashingWriter::new(100);
+        writer.write_all(&data[..40]).unwrap();
+        writer.write_all(&data[40..]).unwrap();
+
+        assert_eq!(writer.written(), 100);
+        assert_eq!(writer.finalize().unwrap(), hash(&data));
+    }
+
+    #[test]
+    fn test_limited_writer_exceeds_limit() {
+        let mut writer = LimitedHashingWriter::new(10);
+        writer.write_all(b"12345678").unwrap();
+
+        let err = writer.write_all(b"abc").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(writer.written(), 8);
+        assert!(writer.finalize().is_err());
+
+        let mut writer = LimitedHashingWriter::new(10);
+        assert!(writer.write_all(&[0_u8; 11]).is_err());
+        assert!(writer.write_all(b"1").is_err());
+    }
+}
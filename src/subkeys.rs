@@ -0,0 +1,70 @@
+use alloc::vec::Vec;
+
+use crate::hkdf::{hkdf_expand, MAX_OUTPUT_LEN};
+use crate::hmac;
+
+/// Derives one subkey of `each_len` bytes per label from a single key.
+///
+/// This is essentially multi-label HKDF-Expand with `key` as the PRK and each
+/// label as the info. Subkeys of up to 32 bytes are `hmac(label, key)`
+/// truncated to `each_len`; longer ones run the full HKDF-Expand counter loop
+/// (RFC 5869 section 2.3). Note the two forms differ, so a 32-byte subkey is
+/// not a prefix of a 64-byte one for the same label. Distinct labels give
+/// independent subkeys.
+///
+/// # Panics
+///
+/// Panics if `each_len` exceeds `255 * 32`, the HKDF-Expand limit.
+pub fn derive_subkeys(key: &[u8], labels: &[&[u8]], each_len: usize) -> Vec<Vec<u8>> {
+    assert!(each_len <= MAX_OUTPUT_LEN, "subkey length {} exceeds the HKDF-Expand limit", each_len);
+
+    labels
+        .iter()
+        .map(|label| {
+            if each_len <= 32 {
+                let mut subkey = hmac(label, key);
+                subkey.truncate(each_len);
+                subkey
+            } else {
+                hkdf_expand(key, label, each_len).expect("length checked against the HKDF-Expand limit")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode;
+
+    #[test]
+    fn test_derive_subkeys_short() {
+        let labels: [&[u8]; 3] = [b"encryption", b"authentication", b"iv"];
+        let subkeys = derive_subkeys(b"master key", &labels, 16);
+
+        assert_eq!(subkeys, derive_subkeys(b"master key", &labels, 16));
+        assert_eq!(subkeys[0], hmac(b"encryption", b"master key")[..16].to_vec());
+        assert_ne!(subkeys[0], subkeys[1]);
+        assert_ne!(subkeys[1], subkeys[2]);
+        assert!(subkeys.iter().all(|subkey| subkey.len() == 16));
+    }
+
+    #[test]
+    fn test_derive_subkeys_expanded() {
+        let labels: [&[u8]; 2] = [b"encryption", b"authentication"];
+        let subkeys = derive_subkeys(b"master key", &labels, 48);
+
+        // HKDF-Expand(PRK = "master key", info = "encryption", L = 48) computed independently
+        let expected = "55809438fa31cdcdf20d1823dbf7c78009e9e92baa0d22ed2a7382ed5da3742f\
+                        fa6efddfcd2d6b357c8ff16306c48f0d";
+        assert_eq!(encode(&subkeys[0]), expected);
+        assert_ne!(subkeys[0], subkeys[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the HKDF-Expand limit")]
+    fn test_derive_subkeys_too_long() {
+        let labels: [&[u8]; 1] = [b"encryption"];
+        let _ = derive_subkeys(b"master key", &labels, 255 * 32 + 1);
+    }
+}
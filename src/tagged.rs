@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::hmac;
+use crate::utils::ct_equal;
+
+/// Version byte for tags made with HMAC-SHA-256.
+pub const MAC_VERSION_SHA256: u8 = 1;
+
+// Computes the bare MAC for a version byte, or None if the version is unknown
+fn mac_for_version(data: &[u8], key: &[u8], version: u8) -> Option<Vec<u8>> {
+    match version {
+        MAC_VERSION_SHA256 => Some(hmac(data, key)),
+        _ => None,
+    }
+}
+
+/// Computes a MAC prefixed with a one-byte version identifying its algorithm.
+///
+/// Storing the version with the tag lets `verify_tagged_mac` keep accepting old
+/// tags after new ones move to a different algorithm. Returns
+/// `Error::UnknownVersion` if `version` is not a known version such as
+/// `MAC_VERSION_SHA256`.
+pub fn tagged_mac(data: &[u8], key: &[u8], version: u8) -> Result<Vec<u8>, Error> {
+    let mac = mac_for_version(data, key, version).ok_or(Error::UnknownVersion { version })?;
+    let mut tagged = Vec::with_capacity(1 + mac.len());
+    tagged.push(version);
+    tagged.extend_from_slice(&mac);
+    Ok(tagged)
+}
+
+/// Verifies a tag from `tagged_mac` using the algorithm its version byte names.
+///
+/// An empty tag or an unknown version verifies as `false`. The MAC itself is
+/// compared in constant time; the version byte is public.
+#[must_use]
+pub fn verify_tagged_mac(data: &[u8], stored: &[u8], key: &[u8]) -> bool {
+    let Some((&version, received)) = stored.split_first() else {
+        return false;
+    };
+    match mac_for_version(data, key, version) {
+        Some(expected) => ct_equal(&expected, received),
+        None => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_mac_current_version() {
+        let tag = tagged_mac(b"stored record", b"key", MAC_VERSION_SHA256).unwrap();
+
+        assert_eq!(tag[0], MAC_VERSION_SHA256);
+        assert_eq!(tag[1..], hmac(b"stored record", b"key"));
+        assert!(verify_tagged_mac(b"stored record", &tag, b"key"));
+        assert!(!verify_tagged_mac(b"other record", &tag, b"key"));
+    }
+
+    #[test]
+    fn test_unknown_version_rejected() {
+        let mut tag = tagged_mac(b"stored record", b"key", MAC_VERSION_SHA256).unwrap();
+        tag[0] = 0xff;
+
+        assert!(!verify_tagged_mac(b"stored record", &tag, b"key"));
+        assert!(!verify_tagged_mac(b"stored record", &[], b"key"));
+    }
+
+    #[test]
+    fn test_tagged_mac_unknown_version() {
+        assert_eq!(
+            tagged_mac(b"stored record", b"key", 0xff),
+            Err(Error::UnknownVersion { version: 0xff })
+        );
+    }
+}
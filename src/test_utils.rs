@@ -0,0 +1,37 @@
+use crate::{hash, Sha256};
+
+/// Asserts that streaming `data` through `Sha256` agrees with the one-shot `hash()`.
+///
+/// The data is fed split at every possible boundary, and once a byte at a time.
+/// Crates that buffer input before handing it to the hasher can call this from
+/// their own tests. Panics naming the first split point that disagrees.
+pub fn assert_streaming_consistency(data: &[u8]) {
+    let expected = hash(data);
+
+    for split in 0..=data.len() {
+        let mut hasher = Sha256::new();
+        hasher.update(&data[..split]);
+        hasher.update(&data[split..]);
+        assert_eq!(hasher.finalize(), expected, "streaming digest differs when split at byte {}", split);
+    }
+
+    let mut hasher = Sha256::new();
+    for byte in data {
+        hasher.update(core::slice::from_ref(byte));
+    }
+    assert_eq!(hasher.finalize(), expected, "streaming digest differs when fed byte by byte");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assert_streaming_consistency() {
+        for len in [0, 1, 55, 56, 64, 65, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+            assert_streaming_consistency(&data);
+        }
+    }
+}
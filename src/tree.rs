@@ -0,0 +1,59 @@
+use rayon::prelude::*;
+
+use crate::{hash, hash_fixed, OUTPUT_SIZE};
+
+/// Hashes `data` as a two-level Merkle tree, hashing the leaves in parallel.
+///
+/// The input is split into `leaf_size`-byte leaves (the last may be shorter),
+/// each leaf is hashed with SHA-256 on the rayon pool, and the root is the
+/// SHA-256 of the leaf digests concatenated in order. **This is not SHA-256 of
+/// `data`**: the output differs from `hash(data)` and only matches another
+/// `hash_tree` with the same `leaf_size`. It is deterministic whatever the
+/// thread count. Empty input has no leaves, so its root is `hash(b"")`.
+///
+/// Panics if `leaf_size` is zero.
+#[must_use]
+pub fn hash_tree(data: &[u8], leaf_size: usize) -> Vec<u8> {
+    assert!(leaf_size > 0, "leaf_size must be nonzero");
+
+    let leaves: Vec<[u8; OUTPUT_SIZE]> = data.par_chunks(leaf_size).map(hash_fixed).collect();
+    hash(leaves.concat())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sequential reference for the same construction
+    fn hash_tree_sequential(data: &[u8], leaf_size: usize) -> Vec<u8> {
+        let leaves: Vec<u8> = data.chunks(leaf_size).flat_map(hash_fixed).collect();
+        hash(leaves)
+    }
+
+    #[test]
+    fn test_hash_tree_matches_sequential() {
+        let data: Vec<u8> = (0..10_000_u32).map(|i| (i % 251) as u8).collect();
+        for leaf_size in [1, 64, 1000, 4096, 10_000, 20_000] {
+            assert_eq!(hash_tree(&data, leaf_size), hash_tree_sequential(&data, leaf_size), "leaf size {}", leaf_size);
+        }
+        assert_ne!(hash_tree(&data, 1000), hash(&data));
+        assert_eq!(hash_tree(b"", 64), hash(b""));
+    }
+
+    #[test]
+    fn test_hash_tree_independent_of_thread_count() {
+        let data = vec![0x17_u8; 50_000];
+        let expected = hash_tree(&data, 512);
+        for threads in [1, 2, 7] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            assert_eq!(pool.install(|| hash_tree(&data, 512)), expected, "{} threads", threads);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "leaf_size must be nonzero")]
+    fn test_hash_tree_rejects_zero_leaf_size() {
+        let _ = hash_tree(b"data", 0);
+    }
+}
@@ -1,124 +1,383 @@
-// This function takes the bytes of the message and pads it such that it contains a multiple of 512 many bits
-pub fn pad(data: &[u8]) -> Option<Vec<u8>> {
-    // These are the counts of the additional bits we need to append onto the message
-
-    // The current length of the message in bits
-    let num_bits = data.len() * 8;
-
-    // zero_bits is the smallest non-zero integer satisfying num_bits + 1 + zero_bits \equiv 448 (mod 512)
-    let zero_bits = ((447 - num_bits as i32) % 512 + 512) % 512;
-
-    // Initialize the vector which will contain the message bits along with the padding
-    let mut bit_vec = Vec::with_capacity(num_bits + 1 + zero_bits as usize + 64);
-    
-    // Populate the first section of bit_vector with the bits of the message
-    for &byte in data {
-        // For each byte we use a moving mask to isolate each bit, in each byte
-        for i in 0..8 {
-            // We are checking if performing 'AND' with the byte and the mask
-            // which results itself in a byte is 0 or not. If it is, then the isolated
-            // bit is 0. Otherwise, the isolated bit is 1. 
-            let bit = byte & (1 << (7 - i)) != 0;
-            bit_vec.push(bit);
-        }
-    }
+use alloc::vec::Vec;
 
-    // Append a '1' to the end of the message
-    bit_vec.push(true);
+use crate::error::HashError;
 
-    // Append zero_bits of '0'
-    for _ in 0..zero_bits {
-        bit_vec.push(false);
-    }
+// This function takes the bytes of the message and pads it such that it contains a multiple of 512 many bits.
+// The streaming hasher pads its final block itself, so this one-shot form is the
+// reference that padded_len and the prepadded entry points are checked against
+// Inputs of up to 2^61 - 1 bytes are supported, the most whose bit length fits the
+// 64-bit length field; anything longer is rejected with InputTooLong
+#[cfg(any(test, feature = "portable-simd"))]
+pub fn pad(data: &[u8]) -> Result<Vec<u8>, HashError> {
+    let bit_len = bit_length(data.len() as u64)?;
+    let total_len = padded_len(data.len());
+    let mut padded_message = Vec::with_capacity(total_len);
+    padded_message.extend_from_slice(data);
 
-    // We need to get the length of the original message and encode it in 64 bits
-    let length_bits = (num_bits as u64).to_be_bytes();
-    for &byte in &length_bits {
-        for i in 0..8 {
-            let bit = byte & (1 << (7 - i)) != 0;
-            bit_vec.push(bit);
-        }
-    }
+    // Append a '1' bit followed by seven '0' bits
+    padded_message.push(0x80);
+
+    // Append zero bytes up to the start of the length field
+    padded_message.resize(total_len - 8, 0);
+
+    // Encode the length of the original message in bits as 64 big-endian bits
+    padded_message.extend_from_slice(&bit_len.to_be_bytes());
 
-    // Confirm the resultant bit_vec is a multiple 512
-    if bit_vec.len() % 512 != 0 {
-        println!("Error: Number of bits is not multiple of 512");
-        return None;
+    // Confirm the result is a whole number of 512-bit blocks
+    if !padded_message.len().is_multiple_of(64) {
+        return Err(HashError::UnalignedPadding { bits: padded_message.len() * 8 });
     }
 
-    // Create new vector of bytes to hold the padded message
-    let num_bytes = bit_vec.len() / 8;
-    let mut padded_message: Vec<u8> = Vec::with_capacity(num_bytes);
+    Ok(padded_message)
+}
 
-    // Interate over each block (chunk) of 8 bits in bit_vec
-    for chunk in bit_vec.chunks(8) {
-        // This byte will be constructed bit by bit
-        let mut byte = 0_u8;
+// Returns the message length in bits, or InputTooLong if it doesn't fit in 64 bits
+pub fn bit_length(len: u64) -> Result<u64, HashError> {
+    len.checked_mul(8).ok_or(HashError::InputTooLong { len })
+}
 
-        // enumerate() provides both an index and the value at the index
-        for (i, &bit) in chunk.iter().enumerate() {
-            // If the bit is 1 (true), then we set that bit 
-            if bit {
-                // Using the bitwise OR assignment operator
-                byte |= 1 << (7 - i);
-            }
-        }
-        padded_message.push(byte);
+/// Returns the length in bytes that `pad()` produces for an input of `input_len` bytes.
+///
+/// That is the input plus the 0x80 byte and the 8-byte length field, rounded
+/// up to a whole number of 64-byte blocks. Nothing is allocated, and it is a
+/// `const fn` so buffers can be sized with it at compile time.
+pub const fn padded_len(input_len: usize) -> usize {
+    (input_len + 9).div_ceil(64) * 64
+}
+
+// Pads a bit-oriented message: only the first bit_len bits of data are the message.
+// The '1' bit goes right after them and the length field holds bit_len
+pub fn pad_bits(data: &[u8], bit_len: usize) -> Vec<u8> {
+    let full_bytes = bit_len / 8;
+    let extra_bits = bit_len % 8;
+
+    let mut padded = Vec::with_capacity(padded_len(full_bytes + 1));
+    padded.extend_from_slice(&data[..full_bytes]);
+
+    // Keep the leading bits of a partial final byte and set the bit right after them
+    if extra_bits > 0 {
+        let mask = 0xff_u8 << (8 - extra_bits);
+        padded.push((data[full_bytes] & mask) | (0x80 >> extra_bits));
+    } else {
+        padded.push(0x80);
+    }
+
+    while padded.len() % 64 != 56 {
+        padded.push(0);
     }
-    
-    Some(padded_message)
+    padded.extend_from_slice(&(bit_len as u64).to_be_bytes());
+    padded
 }
 
-// Parses the padded message into 512-bit blocks represented as a vector of arrays (blocks) each containing 16 u32's
-pub fn parse(data: &[u8]) -> Vec<[u32; 16]> {
+// Returns just the bytes pad would append to a message of len bytes: 0x80, the zero
+// bytes, and the 64-bit big-endian bit length. Lengths past MAX_INPUT_LEN are InputTooLong
+pub fn padding(len: u64) -> Result<Vec<u8>, HashError> {
+    let bit_len = bit_length(len)?;
+    let zero_bytes = (119 - (len % 64) as usize) % 64;
+    let mut tail = Vec::with_capacity(1 + zero_bytes + 8);
+    tail.push(0x80);
+    tail.resize(1 + zero_bytes, 0);
+    tail.extend_from_slice(&bit_len.to_be_bytes());
+    Ok(tail)
+}
+
+// Parses the padded message into 512-bit blocks represented as a vector of arrays (blocks) each containing 16 u32's.
+// The length is checked up front, so input that isn't a whole number of blocks is an error rather than a panic
+pub fn parse(data: &[u8]) -> Result<Vec<[u32; 16]>, HashError> {
+    if !data.len().is_multiple_of(64) {
+        return Err(HashError::UnalignedInput { len: data.len() });
+    }
+
     // Initialize the vector to hold the blocks
     let num_blocks = (data.len() * 8) / 512;
     let mut message_blocks: Vec<[u32; 16]> = Vec::with_capacity(num_blocks);
 
     // Break data into chunks of 64 bytes (16 u32s)
     for outer_chunk in data.chunks(64) {
-        let mut block: [u32; 16] = [0_u32; 16];
+        message_blocks.push(parse_block(outer_chunk));
+    }
 
-        // Iterate over each group of 4 bytes (inner chunk) with in the 64-byte outer chunk
-        for (i, inner_chunk) in outer_chunk.chunks(4).enumerate() {
-            // Create u32 out of the 4 bytes in inner_chunk
-            block[i] = u32::from_be_bytes([inner_chunk[0], inner_chunk[1], inner_chunk[2], inner_chunk[3]]);
-        }
-        message_blocks.push(block);
+    Ok(message_blocks)
+}
+
+// Parses a single 64-byte chunk into a block of 16 big-endian u32's
+pub fn parse_block(chunk: &[u8]) -> [u32; 16] {
+    let mut block: [u32; 16] = [0_u32; 16];
+
+    // Iterate over each group of 4 bytes (inner chunk) with in the 64-byte chunk
+    for (i, inner_chunk) in chunk.chunks(4).enumerate() {
+        // Create u32 out of the 4 bytes in inner_chunk
+        block[i] = u32::from_be_bytes([inner_chunk[0], inner_chunk[1], inner_chunk[2], inner_chunk[3]]);
     }
 
-    message_blocks
+    block
+}
+
+// Constant-time equality shared by every verification path. subtle already avoids
+// data-dependent branches, but the result is also passed through black_box before
+// the final == 1 check, so that even under aggressive LTO the optimizer cannot see
+// through the accumulated difference and reintroduce an early exit.
+// Slices of different lengths compare unequal; the lengths themselves are public.
+pub fn ct_equal(a: &[u8], b: &[u8]) -> bool {
+    use subtle::ConstantTimeEq;
+    let result = core::hint::black_box(a.ct_eq(b).unwrap_u8());
+    result == 1
 }
 
 // ============== Operations on Words ================== //
-fn rotr(x: u32, n: usize) -> u32 {
-    (x >> n) | (x << (32 - n))
+const fn rotr(x: u32, n: usize) -> u32 {
+    x.rotate_right(n as u32)
 }
 
-fn shr(x: u32, n: usize) -> u32 {
+const fn shr(x: u32, n: usize) -> u32 {
     x >> n
 }
 
-pub fn ch(x: u32, y: u32, z: u32) -> u32 {
+pub const fn ch(x: u32, y: u32, z: u32) -> u32 {
     (x & y) ^ (!x & z)
 }
 
-pub fn maj(x: u32, y: u32, z: u32) -> u32 {
+pub const fn maj(x: u32, y: u32, z: u32) -> u32 {
     (x & y) ^ (x & z) ^ (y & z)
 }
 
-pub fn Sigma_256_0(x: u32) -> u32 {
+pub const fn Sigma_256_0(x: u32) -> u32 {
     rotr(x, 2) ^ rotr(x, 13) ^ rotr(x, 22)
 }
 
-pub fn Sigma_256_1(x: u32) -> u32 {
+pub const fn Sigma_256_1(x: u32) -> u32 {
     rotr(x, 6) ^ rotr(x, 11) ^ rotr(x, 25)
 }
 
-pub fn sigma_256_0(x: u32) -> u32 {
+pub const fn sigma_256_0(x: u32) -> u32 {
     rotr(x, 7) ^ rotr(x, 18) ^ shr(x, 3)
 }
 
-pub fn sigma_256_1(x: u32) -> u32 {
+pub const fn sigma_256_1(x: u32) -> u32 {
     rotr(x, 17) ^ rotr(x, 19) ^ shr(x, 10)
-}
\ No newline at end of file
+}
+
+// ============== Operations on 64-bit Words (SHA-512) ================== //
+#[cfg(feature = "sha512")]
+pub fn ch64(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (!x & z)
+}
+
+#[cfg(feature = "sha512")]
+pub fn maj64(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+#[cfg(feature = "sha512")]
+pub fn Sigma_512_0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+#[cfg(feature = "sha512")]
+pub fn Sigma_512_1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+#[cfg(feature = "sha512")]
+pub fn sigma_512_0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+#[cfg(feature = "sha512")]
+pub fn sigma_512_1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_padded_len_boundaries() {
+        assert_eq!(padded_len(0), 64);
+        assert_eq!(padded_len(55), 64);
+        assert_eq!(padded_len(56), 128);
+        assert_eq!(padded_len(63), 128);
+        assert_eq!(padded_len(64), 128);
+        assert_eq!(padded_len(119), 128);
+        assert_eq!(padded_len(120), 192);
+    }
+
+    // The original bit-by-bit implementation of pad, kept to check the byte version against
+    fn pad_with_bools(data: &[u8]) -> Result<Vec<u8>, HashError> {
+        // These are the counts of the additional bits we need to append onto the message
+
+        // The current length of the message in bits
+        let num_bits = data.len() * 8;
+
+        // zero_bits is the smallest non-zero integer satisfying num_bits + 1 + zero_bits \equiv 448 (mod 512)
+        let zero_bits = ((447 - num_bits as i32) % 512 + 512) % 512;
+
+        // Initialize the vector which will contain the message bits along with the padding
+        let mut bit_vec = Vec::with_capacity(num_bits + 1 + zero_bits as usize + 64);
+
+        // Populate the first section of bit_vector with the bits of the message
+        for &byte in data {
+            // For each byte we use a moving mask to isolate each bit, in each byte
+            for i in 0..8 {
+                // We are checking if performing 'AND' with the byte and the mask
+                // which results itself in a byte is 0 or not. If it is, then the isolated
+                // bit is 0. Otherwise, the isolated bit is 1. 
+                let bit = byte & (1 << (7 - i)) != 0;
+                bit_vec.push(bit);
+            }
+        }
+
+        // Append a '1' to the end of the message
+        bit_vec.push(true);
+
+        // Append zero_bits of '0'
+        bit_vec.resize(bit_vec.len() + zero_bits as usize, false);
+
+        // We need to get the length of the original message and encode it in 64 bits
+        let length_bits = (num_bits as u64).to_be_bytes();
+        for &byte in &length_bits {
+            for i in 0..8 {
+                let bit = byte & (1 << (7 - i)) != 0;
+                bit_vec.push(bit);
+            }
+        }
+
+        // Confirm the resultant bit_vec is a multiple 512
+        if bit_vec.len() % 512 != 0 {
+            return Err(HashError::UnalignedPadding { bits: bit_vec.len() });
+        }
+
+        // Create new vector of bytes to hold the padded message
+        let num_bytes = bit_vec.len() / 8;
+        let mut padded_message: Vec<u8> = Vec::with_capacity(num_bytes);
+
+        // Interate over each block (chunk) of 8 bits in bit_vec
+        for chunk in bit_vec.chunks(8) {
+            // This byte will be constructed bit by bit
+            let mut byte = 0_u8;
+
+            // enumerate() provides both an index and the value at the index
+            for (i, &bit) in chunk.iter().enumerate() {
+                // If the bit is 1 (true), then we set that bit 
+                if bit {
+                    // Using the bitwise OR assignment operator
+                    byte |= 1 << (7 - i);
+                }
+            }
+            padded_message.push(byte);
+        }
+
+        Ok(padded_message)
+    }
+
+    #[test]
+    fn test_padded_len_matches_pad() {
+        for len in 0..300 {
+            assert_eq!(padded_len(len), pad(&vec![0_u8; len]).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn test_pad_matches_bool_implementation() {
+        for len in [0, 1, 54, 55, 56, 57, 63, 64, 65, 119, 120, 128, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 13 + 5) as u8).collect();
+            assert_eq!(pad(&data).unwrap(), pad_with_bools(&data).unwrap(), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn test_rotr_full_range() {
+        let x = 0x8000_0001_u32;
+
+        assert_eq!(rotr(x, 0), x);
+        assert_eq!(rotr(x, 1), 0xc000_0000);
+        assert_eq!(rotr(x, 32), x);
+    }
+
+    #[test]
+    fn test_bit_length_overflow() {
+        assert_eq!(bit_length(3), Ok(24));
+        assert_eq!(bit_length(u64::MAX / 8), Ok(u64::MAX - 7));
+        assert_eq!(bit_length(u64::MAX / 8 + 1), Err(HashError::InputTooLong { len: u64::MAX / 8 + 1 }));
+    }
+
+    #[test]
+    fn test_padding_rejects_oversized_length() {
+        assert_eq!(padding(3).unwrap().len(), 61);
+        assert_eq!(padding(u64::MAX / 8).unwrap().len(), 65);
+        assert_eq!(padding(u64::MAX / 8 + 1), Err(HashError::InputTooLong { len: u64::MAX / 8 + 1 }));
+    }
+
+    #[test]
+    fn test_pad_error_converts() {
+        assert!(pad(b"abc").is_ok());
+
+        let err = HashError::UnalignedPadding { bits: 100 };
+        assert_eq!(Error::from(err.clone()), Error::Hash(err));
+    }
+
+    #[test]
+    fn test_pad_bits_whole_bytes_matches_pad() {
+        for len in [0, 1, 55, 56, 64, 100] {
+            let data = vec![0x6b_u8; len];
+            assert_eq!(pad_bits(&data, len * 8), pad(&data).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_pad_bits_masks_partial_byte() {
+        // Five message bits 01101 followed by the '1' pad bit, the rest of the byte cleared
+        let padded = pad_bits(&[0x6f], 5);
+        assert_eq!(padded.len(), 64);
+        assert_eq!(padded[0], 0b0110_1100);
+        assert_eq!(padded[63], 5);
+    }
+
+    #[test]
+    fn test_padding_matches_pad() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 119, 120] {
+            let data = vec![0xab_u8; len];
+            let padded = pad(&data).unwrap();
+
+            assert_eq!(padding(len as u64).unwrap(), padded[len..].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_ct_equal() {
+        let tag = [0x5a_u8; 32];
+        let mut other = tag;
+
+        assert!(ct_equal(&tag, &other));
+        other[31] ^= 1;
+        assert!(!ct_equal(&tag, &other));
+        other[31] ^= 1;
+        other[0] ^= 0x80;
+        assert!(!ct_equal(&tag, &other));
+        assert!(!ct_equal(&tag, &tag[..16]));
+        assert!(ct_equal(&[], &[]));
+    }
+
+    #[test]
+    fn test_parse_aligned() {
+        let mut data = vec![0_u8; 128];
+        data[0..4].copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+        data[124..128].copy_from_slice(&[0xaa, 0xbb, 0xcc, 0xdd]);
+        let blocks = parse(&data).unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0][0], 0x01020304);
+        assert_eq!(blocks[1][15], 0xaabbccdd);
+        assert_eq!(parse(&[]).unwrap(), Vec::<[u32; 16]>::new());
+    }
+
+    #[test]
+    fn test_parse_misaligned() {
+        for len in [1, 4, 63, 65, 100] {
+            assert_eq!(parse(&vec![0_u8; len]), Err(HashError::UnalignedInput { len }));
+        }
+    }
+}
@@ -0,0 +1,6 @@
+// Checks that misuse of the consuming APIs is rejected at compile time
+#[test]
+fn test_compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "std")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use bernie_hmac::{hash, hash_file, hash_file_digest, hmac, hmac_file, hmac_file_digest, Digest, Tag};
+
+// Writes contents to a file in the temp directory that is unique to this test
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bernie_hmac_{}_{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_hash_file_matches_hash() {
+    let contents: Vec<u8> = (0..20_000_u32).map(|i| (i % 253) as u8).collect();
+    let path = temp_file("hash_file", &contents);
+
+    let expected: [u8; 32] = hash(&contents).try_into().unwrap();
+    assert_eq!(hash_file(&path).unwrap(), expected.to_vec());
+    assert_eq!(hash_file_digest(&path).unwrap(), Digest::from(expected));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_hmac_file_matches_hmac() {
+    let key = b"file key";
+    let contents = b"file contents that are MACed".repeat(500);
+    let path = temp_file("hmac_file", &contents);
+
+    let expected: [u8; 32] = hmac(&contents, key).try_into().unwrap();
+    assert_eq!(hmac_file(&path, key).unwrap(), expected.to_vec());
+    assert_eq!(hmac_file_digest(&path, key).unwrap(), Tag::from(expected));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_empty_and_missing_files() {
+    let path = temp_file("empty", b"");
+    assert_eq!(hash_file_digest(&path).unwrap(), Digest::from(<[u8; 32]>::try_from(hash(b"")).unwrap()));
+    fs::remove_file(&path).unwrap();
+
+    assert!(hash_file_digest(&path).is_err());
+    assert!(hmac_file_digest(&path, b"key").is_err());
+}
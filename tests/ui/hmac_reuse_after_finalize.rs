@@ -0,0 +1,10 @@
+use bernie_hmac::Hmac;
+
+fn main() {
+    let mut mac = Hmac::new(b"key");
+    mac.update(b"message");
+    let _tag = mac.finalize();
+
+    // A consumed context can't produce a second tag
+    let _again = mac.finalize();
+}
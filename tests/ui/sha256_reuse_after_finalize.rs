@@ -0,0 +1,10 @@
+use bernie_hmac::Sha256;
+
+fn main() {
+    let mut hasher = Sha256::new();
+    hasher.update(b"abc");
+    let _digest = hasher.finalize();
+
+    // finalize took the hasher by value, so it can't be fed again
+    hasher.update(b"more");
+}
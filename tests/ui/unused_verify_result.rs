@@ -0,0 +1,6 @@
+#![deny(unused_must_use)]
+
+fn main() {
+    // Dropping the result of a verification is almost certainly a bug
+    bernie_hmac::verify_hmac(b"data", b"tag", b"key");
+}